@@ -1,4 +1,4 @@
-use crate::{NullTag, Shared, Shield, Tag};
+use crate::{tag, NullTag, Shared, Shield, Tag};
 use core::{
     fmt,
     marker::PhantomData,
@@ -17,29 +17,26 @@ where
 ///
 /// This struct provides methods for manipulating the atomic pointer via
 /// standard atomic operations using `Shared` as the corresponding non atomic version.
+///
+/// Only thin pointers fit in the single word this is backed by so `V` must be `Sized`,
+/// see `Shared` for how to store trait objects and slices.
 #[repr(transparent)]
-pub struct Atomic<V, T1 = NullTag, T2 = NullTag>
-where
-    T1: Tag,
-    T2: Tag,
-{
+pub struct Atomic<V, T1 = NullTag, T2 = NullTag> {
     pub(crate) data: AtomicUsize,
     _m0: PhantomData<V>,
     _m1: PhantomData<T1>,
     _m2: PhantomData<T2>,
 }
 
-impl<V, T1, T2> Atomic<V, T1, T2>
-where
-    T1: Tag,
-    T2: Tag,
-{
+// This impl block has no trait bounds so that the constructors
+// can be `const fn` on our MSRV which allows placing an `Atomic` in a `static`.
+impl<V, T1, T2> Atomic<V, T1, T2> {
     /// Constructs an `Atomic` from a raw tagged pointer represented as an integer.
     ///
     /// # Safety
     /// Marked unsafe because this is not usually what the user wants.
     /// `Atomic::null` should be preferred when possible.
-    pub unsafe fn from_raw(raw: usize) -> Self {
+    pub const unsafe fn from_raw(raw: usize) -> Self {
         Self {
             data: AtomicUsize::new(raw),
             _m0: PhantomData,
@@ -48,6 +45,17 @@ where
         }
     }
 
+    /// Constructs a new `Atomic` with a null value.
+    pub const fn null() -> Self {
+        unsafe { Self::from_raw(0) }
+    }
+}
+
+impl<V, T1, T2> Atomic<V, T1, T2>
+where
+    T1: Tag,
+    T2: Tag,
+{
     /// Constructs a new `Atomic` from a tagged pointer.
     ///
     /// # Safety
@@ -56,17 +64,15 @@ where
         unsafe { Self::from_raw(shared.into_raw()) }
     }
 
-    /// Constructs a new `Atomic` with a null value.
-    pub fn null() -> Self {
-        unsafe { Self::from_raw(0) }
-    }
-
     /// This constructs a `Vec<Atomic>` with null values in an optimized manner.
     pub fn null_vec(len: usize) -> Vec<Self> {
         unsafe { mem::transmute(vec![0_usize; len]) }
     }
 
     /// Load a the tagged pointer.
+    ///
+    /// # Panics
+    /// Panics if `ordering` is `Release` or `AcqRel`, just like `AtomicUsize::load`.
     pub fn load<'collector, 'shield, S>(
         &self,
         ordering: Ordering,
@@ -79,7 +85,22 @@ where
         unsafe { Shared::from_raw(raw) }
     }
 
+    /// Load the tagged pointer and split it into the pointer with all tags stripped and the two tags.
+    pub fn load_decomposed<'collector, 'shield, S>(
+        &self,
+        ordering: Ordering,
+        shield: &'shield S,
+    ) -> (Shared<'shield, V, T1, T2>, T1, T2)
+    where
+        S: Shield<'collector>,
+    {
+        self.load(ordering, shield).decompose()
+    }
+
     /// Store a tagged pointer, replacing the previous value.
+    ///
+    /// # Panics
+    /// Panics if `ordering` is `Acquire` or `AcqRel`, just like `AtomicUsize::store`.
     pub fn store(&self, data: Shared<'_, V, T1, T2>, ordering: Ordering) {
         let raw = data.into_raw();
         self.data.store(raw, ordering);
@@ -100,6 +121,18 @@ where
         unsafe { Shared::from_raw(old_raw) }
     }
 
+    /// Take the stored tagged pointer, leaving a null pointer without tags in its place.
+    pub fn take<'collector, 'shield, S>(
+        &self,
+        ordering: Ordering,
+        shield: &'shield S,
+    ) -> Shared<'shield, V, T1, T2>
+    where
+        S: Shield<'collector>,
+    {
+        self.swap(Shared::null(), ordering, shield)
+    }
+
     /// Conditionally swap the stored tagged pointer, always returns the previous value.
     pub fn compare_and_swap<'collector, 'shield, S>(
         &self,
@@ -120,6 +153,9 @@ where
     /// Conditionally exchange the stored tagged pointer, always returns
     /// the previous value and a result indicating if it was written or not.
     /// On success this value is guaranteed to be equal to current.
+    ///
+    /// # Panics
+    /// Panics if `failure` is `Release` or `AcqRel`, just like `AtomicUsize::compare_exchange`.
     pub fn compare_exchange<'collector, 'shield, S>(
         &self,
         current: Shared<'_, V, T1, T2>,
@@ -146,6 +182,9 @@ where
     ///
     /// This variant may spuriously fail on platforms where LL/SC is used.
     /// This allows more efficient code generation on those platforms.
+    ///
+    /// # Panics
+    /// Panics if `failure` is `Release` or `AcqRel`, just like `AtomicUsize::compare_exchange_weak`.
     pub fn compare_exchange_weak<'collector, 'shield, S>(
         &self,
         current: Shared<'_, V, T1, T2>,
@@ -165,6 +204,72 @@ where
 
         map_both(result, |raw| unsafe { Shared::from_raw(raw) })
     }
+
+    /// Conditionally exchange the stored tagged pointer like `Atomic::compare_exchange`
+    /// but with the pointers and their tags passed and returned separately.
+    /// This is the counterpart of `Atomic::load_decomposed`.
+    ///
+    /// Any tag bits set in the pointers are overwritten by the accompanying tags.
+    ///
+    /// # Panics
+    /// Panics if `failure` is `Release` or `AcqRel`, just like `AtomicUsize::compare_exchange`.
+    #[allow(clippy::type_complexity)]
+    pub fn compare_exchange_decomposed<'collector, 'shield, S>(
+        &self,
+        current: (Shared<'_, V, T1, T2>, T1, T2),
+        new: (Shared<'_, V, T1, T2>, T1, T2),
+        success: Ordering,
+        failure: Ordering,
+        shield: &'shield S,
+    ) -> Result<(Shared<'shield, V, T1, T2>, T1, T2), (Shared<'shield, V, T1, T2>, T1, T2)>
+    where
+        S: Shield<'collector>,
+    {
+        let (current_ptr, current_lo, current_hi) = current;
+        let (new_ptr, new_lo, new_hi) = new;
+        let current = current_ptr.with_tag_lo(current_lo).with_tag_hi(current_hi);
+        let new = new_ptr.with_tag_lo(new_lo).with_tag_hi(new_hi);
+        let result = self.compare_exchange(current, new, success, failure, shield);
+        map_both(result, Shared::decompose)
+    }
+
+    /// Set the bits that are set in `tag` in the low tag position, returning the previous value.
+    /// The pointer and any other bits are left untouched.
+    ///
+    /// In debug builds this panics if the alignment of `V` doesn't free up enough low bits for the tag.
+    pub fn fetch_or_tag_lo<'collector, 'shield, S>(
+        &self,
+        tag: T1,
+        ordering: Ordering,
+        _shield: &'shield S,
+    ) -> Shared<'shield, V, T1, T2>
+    where
+        S: Shield<'collector>,
+    {
+        debug_assert!(
+            tag::fits_lo::<V, T1>(),
+            "low tag does not fit in the pointer"
+        );
+        let mask = tag::set_tag::<T1>(0, tag.serialize(), tag::TagPosition::Lo);
+        let old_raw = self.data.fetch_or(mask, ordering);
+        unsafe { Shared::from_raw(old_raw) }
+    }
+
+    /// Set the bits that are set in `tag` in the high tag position, returning the previous value.
+    /// The pointer and any other bits are left untouched.
+    pub fn fetch_or_tag_hi<'collector, 'shield, S>(
+        &self,
+        tag: T2,
+        ordering: Ordering,
+        _shield: &'shield S,
+    ) -> Shared<'shield, V, T1, T2>
+    where
+        S: Shield<'collector>,
+    {
+        let mask = tag::set_tag::<T2>(0, tag.serialize(), tag::TagPosition::Hi);
+        let old_raw = self.data.fetch_or(mask, ordering);
+        unsafe { Shared::from_raw(old_raw) }
+    }
 }
 
 unsafe impl<V, T1, T2> Send for Atomic<V, T1, T2>
@@ -188,13 +293,42 @@ where
 {
 }
 
+impl<V, T1, T2> Default for Atomic<V, T1, T2>
+where
+    T1: Tag,
+    T2: Tag,
+{
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+impl<'shield, V, T1, T2> From<Shared<'shield, V, T1, T2>> for Atomic<V, T1, T2>
+where
+    T1: Tag,
+    T2: Tag,
+{
+    fn from(shared: Shared<'shield, V, T1, T2>) -> Self {
+        Self::new(shared)
+    }
+}
+
+impl<V, T1, T2> From<*mut V> for Atomic<V, T1, T2>
+where
+    T1: Tag,
+    T2: Tag,
+{
+    fn from(ptr: *mut V) -> Self {
+        unsafe { Self::from_raw(ptr as usize) }
+    }
+}
+
 impl<V, T1, T2> fmt::Debug for Atomic<V, T1, T2>
 where
     T1: Tag,
     T2: Tag,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use crate::tag;
         let data = self.data.load(Ordering::SeqCst);
         let lo = tag::read_tag::<T1>(data, tag::TagPosition::Lo);
         let hi = tag::read_tag::<T2>(data, tag::TagPosition::Hi);
@@ -206,3 +340,115 @@ where
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Atomic;
+    use crate::{unprotected, NullTag, Shared, Tag};
+    use core::sync::atomic::Ordering;
+    use generic_array::{typenum::U1, GenericArray};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Flag(bool);
+
+    impl Tag for Flag {
+        type Size = U1;
+
+        fn deserialize(bits: GenericArray<bool, Self::Size>) -> Self {
+            Flag(bits[0])
+        }
+
+        fn serialize(self) -> GenericArray<bool, Self::Size> {
+            let mut bits = GenericArray::default();
+            bits[0] = self.0;
+            bits
+        }
+    }
+
+    static NULL: Atomic<usize> = Atomic::null();
+    const NULL_SHARED: Shared<'static, usize> = Shared::null();
+
+    #[test]
+    fn const_null() {
+        let shield = unsafe { unprotected() };
+        assert!(NULL.load(Ordering::Relaxed, shield).is_null());
+        assert_eq!(NULL.load(Ordering::Relaxed, shield), NULL_SHARED);
+    }
+
+    #[test]
+    fn conversions() {
+        let shield = unsafe { unprotected() };
+        let atomic: Atomic<usize> = Atomic::default();
+        assert!(atomic.load(Ordering::Relaxed, shield).is_null());
+
+        let atomic: Atomic<usize> = Atomic::from(0x1000 as *mut usize);
+        let shared = atomic.load(Ordering::Relaxed, shield);
+        assert_eq!(shared.into_raw(), 0x1000);
+
+        let atomic: Atomic<usize> = shared.into();
+        assert_eq!(atomic.load(Ordering::Relaxed, shield), shared);
+    }
+
+    #[test]
+    fn take() {
+        let shield = unsafe { unprotected() };
+        let atomic: Atomic<usize> = Atomic::new(unsafe { Shared::from_raw(0x1000) });
+        assert_eq!(atomic.take(Ordering::Relaxed, shield).into_raw(), 0x1000);
+        assert!(atomic.load(Ordering::Relaxed, shield).is_null());
+    }
+
+    #[test]
+    fn compare_exchange_decomposed() {
+        let shield = unsafe { unprotected() };
+        let ptr: Shared<'_, u64, Flag, NullTag> = unsafe { Shared::from_raw(0x1000) };
+        let atomic = Atomic::new(ptr);
+
+        let result = atomic.compare_exchange_decomposed(
+            (ptr, Flag(true), NullTag),
+            (ptr, Flag(false), NullTag),
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            shield,
+        );
+
+        let (current, lo, _) = result.unwrap_err();
+        assert_eq!(current, ptr);
+        assert_eq!(lo, Flag(false));
+
+        let result = atomic.compare_exchange_decomposed(
+            (ptr, Flag(false), NullTag),
+            (ptr, Flag(true), NullTag),
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            shield,
+        );
+
+        let (previous, lo, _) = result.unwrap();
+        assert_eq!(previous, ptr);
+        assert_eq!(lo, Flag(false));
+        assert_eq!(atomic.load(Ordering::Relaxed, shield).into_raw(), 0x1001);
+    }
+
+    #[test]
+    fn boxed_trait_object() {
+        let shield = unsafe { unprotected() };
+        let object: Box<dyn Fn() -> usize> = Box::new(|| 5);
+        let atomic: Atomic<Box<dyn Fn() -> usize>> = Atomic::from(Box::into_raw(Box::new(object)));
+        let shared = atomic.take(Ordering::Relaxed, shield);
+        assert_eq!(unsafe { shared.as_ref_unchecked()() }, 5);
+        drop(unsafe { Box::from_raw(shared.as_ptr()) });
+    }
+
+    #[test]
+    #[should_panic(expected = "release load")]
+    fn load_release() {
+        let shield = unsafe { unprotected() };
+        NULL.load(Ordering::Release, shield);
+    }
+
+    #[test]
+    #[should_panic(expected = "acquire store")]
+    fn store_acquire() {
+        NULL.store(NULL_SHARED, Ordering::Acquire);
+    }
+}
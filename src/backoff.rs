@@ -6,19 +6,54 @@ use std::sync::atomic;
 const SPIN_LIMIT: u32 = 6;
 const YIELD_LIMIT: u32 = 10;
 
+/// Performs exponential backoff in spin loops.
+///
+/// Backing off in a loop that retries a failed compare-and-swap reduces contention on the
+/// contended cache line and improves overall throughput. Once spinning stops being effective
+/// the backoff will instead yield the thread to the OS scheduler when the `std` feature is enabled.
+///
+/// # Examples
+/// ```
+/// use flize::Backoff;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// fn fetch_mul(a: &AtomicUsize, b: usize) -> usize {
+///     let backoff = Backoff::new();
+///
+///     loop {
+///         let val = a.load(Ordering::SeqCst);
+///
+///         if a.compare_and_swap(val, val.wrapping_mul(b), Ordering::SeqCst) == val {
+///             return val;
+///         }
+///
+///         backoff.spin();
+///     }
+/// }
+///
+/// let a = AtomicUsize::new(7);
+/// assert_eq!(fetch_mul(&a, 8), 7);
+/// assert_eq!(a.load(Ordering::SeqCst), 56);
+/// ```
 pub struct Backoff {
     step: Cell<u32>,
 }
 
 impl Backoff {
+    /// Creates a new `Backoff` in its initial state.
     pub fn new() -> Self {
         Self { step: Cell::new(0) }
     }
 
+    /// Resets the `Backoff` to its initial state.
     pub fn reset(&self) {
         self.step.set(0);
     }
 
+    /// Backs off in a lock-free loop.
+    ///
+    /// This should be used when a retry is needed because another thread made progress,
+    /// such as after a failed compare-and-swap. It never yields the thread.
     pub fn spin(&self) {
         for _ in 0..1 << self.step.get().min(SPIN_LIMIT) {
             atomic::spin_loop_hint();
@@ -29,6 +64,10 @@ impl Backoff {
         }
     }
 
+    /// Backs off in a blocking loop.
+    ///
+    /// This should be used when waiting for another thread to make progress.
+    /// It spins for a while and then starts yielding the thread if the `std` feature is enabled.
     pub fn snooze(&self) {
         if self.step.get() <= SPIN_LIMIT {
             for _ in 0..1 << self.step.get() {
@@ -49,6 +88,7 @@ impl Backoff {
         }
     }
 
+    /// Returns true if exponential backoff has completed and blocking the thread is advised.
     pub fn is_completed(&self) -> bool {
         self.step.get() > YIELD_LIMIT
     }
@@ -59,3 +99,34 @@ impl Default for Backoff {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Backoff, SPIN_LIMIT, YIELD_LIMIT};
+
+    #[test]
+    fn snooze_completes() {
+        let backoff = Backoff::new();
+
+        for _ in 0..YIELD_LIMIT {
+            assert!(!backoff.is_completed());
+            backoff.snooze();
+        }
+
+        backoff.snooze();
+        assert!(backoff.is_completed());
+        backoff.reset();
+        assert!(!backoff.is_completed());
+    }
+
+    #[test]
+    fn spin_never_completes() {
+        let backoff = Backoff::new();
+
+        for _ in 0..SPIN_LIMIT * 4 {
+            backoff.spin();
+        }
+
+        assert!(!backoff.is_completed());
+    }
+}
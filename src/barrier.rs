@@ -11,22 +11,24 @@
 //!
 //! When no specialized implementation is available we fall back to executing a normal
 //! sequentially consistent barrier in both the light and heavy barriers.
+//! The fallback is also used under Miri since it can't emulate the OS interfaces.
 //!
 //! THIS MODULE IS FULL OF HACKS THAT BLATANTLY ABUSE OS INTERFACES.
 //! IT'S A TOTAL MESS AND FOR YOUR OWN MENTAL HEALTH YOU MAY WANT TO AVOID THIS CODE.
 //! GIVE A PRAYER TO ANY FUTURE DEVELOPER THAT HAS TO MAINTAIN THIS CODE.
 //! IF IT WORKS THEN DON'T TOUCH IT EH?
 
-#[cfg(all(feature = "fast-barrier", target_os = "windows"))]
+#[cfg(all(feature = "fast-barrier", target_os = "windows", not(miri)))]
 pub use windows::{light_barrier, strong_barrier};
 
-#[cfg(all(feature = "fast-barrier", target_os = "linux"))]
+#[cfg(all(feature = "fast-barrier", target_os = "linux", not(miri)))]
 pub use linux::{light_barrier, strong_barrier};
 
-#[cfg(all(feature = "fast-barrier", target_os = "macos"))]
+#[cfg(all(feature = "fast-barrier", target_os = "macos", not(miri)))]
 pub use macos::{light_barrier, strong_barrier};
 
 #[cfg(any(
+    miri,
     not(feature = "fast-barrier"),
     all(
         not(target_os = "linux"),
@@ -36,7 +38,7 @@ pub use macos::{light_barrier, strong_barrier};
 ))]
 pub use fallback::{light_barrier, strong_barrier};
 
-#[cfg(all(feature = "fast-barrier", target_os = "windows"))]
+#[cfg(all(feature = "fast-barrier", target_os = "windows", not(miri)))]
 mod windows {
     use core::sync::atomic::{compiler_fence, Ordering};
     use winapi::um::processthreadsapi;
@@ -52,7 +54,7 @@ mod windows {
     }
 }
 
-#[cfg(all(feature = "fast-barrier", target_os = "linux"))]
+#[cfg(all(feature = "fast-barrier", target_os = "linux", not(miri)))]
 mod linux {
     use crate::lazy::Lazy;
     use core::sync::atomic::{compiler_fence, fence, Ordering};
@@ -139,7 +141,7 @@ mod linux {
     }
 }
 
-#[cfg(all(feature = "fast-barrier", target_os = "macos"))]
+#[cfg(all(feature = "fast-barrier", target_os = "macos", not(miri)))]
 mod macos {
     use core::ptr::null_mut;
     use core::sync::atomic::{compiler_fence, Ordering};
@@ -189,6 +191,7 @@ mod macos {
 }
 
 #[cfg(any(
+    miri,
     not(feature = "fast-barrier"),
     all(
         not(target_os = "linux"),
@@ -216,7 +219,10 @@ mod tests {
 
     #[test]
     fn mixed_test() {
+        #[cfg(not(miri))]
         const ITER: usize = 10000;
+        #[cfg(miri)]
+        const ITER: usize = 100;
         const DIV_BY_STRONG: usize = 2;
         const THREADS: [usize; 6] = [2, 4, 8, 16, 32, 64];
 
@@ -1,3 +1,4 @@
+use core::fmt;
 use core::ops::{Deref, DerefMut};
 
 /// This struct has a minimum alignment that matches the cache prefetch size on different platforms.
@@ -10,15 +11,20 @@ use core::ops::{Deref, DerefMut};
     not(any(target_arch = "x86_64", target_arch = "aarch64")),
     repr(align(64))
 )]
-#[derive(Debug)]
 pub struct CachePadded<T> {
     value: T,
 }
 
 impl<T> CachePadded<T> {
+    /// Pads and aligns a value to the length of a cache line.
     pub const fn new(value: T) -> Self {
         Self { value }
     }
+
+    /// Returns the inner value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
 }
 
 impl<T> Deref for CachePadded<T> {
@@ -35,6 +41,12 @@ impl<T> DerefMut for CachePadded<T> {
     }
 }
 
+impl<T: fmt::Debug> fmt::Debug for CachePadded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::CachePadded;
@@ -50,4 +62,12 @@ mod tests {
 
         assert_eq!(mem::align_of::<CachePadded<usize>>(), alignment);
     }
+
+    #[test]
+    fn access_inner() {
+        let mut padded = CachePadded::new(vec![1, 2]);
+        padded.push(3);
+        assert_eq!(format!("{:?}", padded), "[1, 2, 3]");
+        assert_eq!(padded.into_inner(), vec![1, 2, 3]);
+    }
 }
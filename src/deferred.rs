@@ -20,7 +20,8 @@ type Data = [usize; DATA_SIZE];
 /// Should it exceed 3 words it will act as a boxed closure.
 pub struct Deferred {
     call: unsafe fn(*mut u8),
-    data: Data,
+    // this is kept uninitialized since a closure smaller than `Data` leaves some of it unwritten
+    data: MaybeUninit<Data>,
     _m0: PhantomData<*mut ()>,
 }
 
@@ -45,7 +46,7 @@ impl Deferred {
 
                 Self {
                     call: call::<F>,
-                    data: data.assume_init(),
+                    data,
                     _m0: PhantomData,
                 }
             } else {
@@ -65,7 +66,7 @@ impl Deferred {
 
                 Self {
                     call: call::<F>,
-                    data: data.assume_init(),
+                    data,
                     _m0: PhantomData,
                 }
             }
@@ -73,7 +74,7 @@ impl Deferred {
     }
 
     pub fn call(mut self) {
-        unsafe { (self.call)(&mut self.data as *mut Data as *mut u8) }
+        unsafe { (self.call)(self.data.as_mut_ptr() as *mut u8) }
     }
 }
 
@@ -0,0 +1,452 @@
+use crate::{Atomic, Backoff, CachePadded, Shared, Shield};
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr;
+use core::sync::atomic::{fence, AtomicIsize, Ordering};
+use std::sync::Arc;
+
+/// The capacity of the buffer a deque starts out with.
+const MIN_CAP: usize = 64;
+
+/// A circular buffer holding the elements of a deque.
+/// Indices wrap around so the buffer doesn't own the values in it, the deque tracks which slots are occupied.
+struct Buffer<T> {
+    /// The start of the allocation.
+    ptr: *mut T,
+
+    /// The number of slots, always a power of two.
+    cap: usize,
+}
+
+impl<T> Buffer<T> {
+    /// Allocates a new buffer that isn't referenced by anything.
+    fn alloc(cap: usize) -> *mut Self {
+        debug_assert_eq!(cap, cap.next_power_of_two());
+        let mut storage = Vec::with_capacity(cap);
+        let ptr = storage.as_mut_ptr();
+        mem::forget(storage);
+        Box::into_raw(Box::new(Self { ptr, cap }))
+    }
+
+    /// Deallocates the buffer without dropping any values in it.
+    ///
+    /// # Safety
+    /// The pointer must have been created by `Buffer::alloc` and may not be used again.
+    unsafe fn destroy(this: *mut Self) {
+        let buffer = Box::from_raw(this);
+        drop(Vec::from_raw_parts(buffer.ptr, 0, buffer.cap));
+    }
+
+    /// Returns a pointer to the slot at the given index.
+    unsafe fn at(&self, index: isize) -> *mut T {
+        self.ptr.offset(index & (self.cap - 1) as isize)
+    }
+
+    unsafe fn write(&self, index: isize, value: T) {
+        ptr::write_volatile(self.at(index), value)
+    }
+
+    /// Reads the value at the given index.
+    /// This may race with the worker overwriting the slot in which case the value must be forgotten.
+    unsafe fn read(&self, index: isize) -> T {
+        ptr::read_volatile(self.at(index))
+    }
+}
+
+/// The state shared between a worker and its stealers.
+struct Inner<T> {
+    /// The index of the oldest element, stealers take from here.
+    front: CachePadded<AtomicIsize>,
+
+    /// The index one past the newest element, the worker pushes and pops here.
+    back: CachePadded<AtomicIsize>,
+
+    /// The current buffer, replaced and retired by the worker when it grows.
+    buffer: CachePadded<Atomic<Buffer<T>>>,
+
+    /// Indicates that dropping an `Inner<T>` may drop values of type `T`.
+    _m0: PhantomData<*mut T>,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        // we have exclusive access to the deque so nothing can be reading the buffer
+        unsafe {
+            crate::unprotected_scope(|shield| {
+                let front = self.front.load(Ordering::Relaxed);
+                let back = self.back.load(Ordering::Relaxed);
+                let buffer = self.buffer.load(Ordering::Relaxed, shield);
+                let buffer_ref = buffer.as_ref_unchecked();
+
+                for index in front..back {
+                    ptr::drop_in_place(buffer_ref.at(index));
+                }
+
+                Buffer::destroy(buffer.as_ptr());
+            });
+        }
+    }
+}
+
+/// The owner side of a lock-free work-stealing deque based on the algorithm described by
+/// David Chase and Yossi Lev.
+///
+/// The worker pushes and pops values at the back of the deque in LIFO order
+/// while any number of [`Stealer`]s take values from the front.
+/// The buffer grows when it fills up and the previous one is retired through the shield used for the push
+/// since stealers may still be reading from it.
+///
+/// A worker can be sent to another thread but can't be shared between threads.
+///
+/// # Examples
+/// ```
+/// use flize::{Collector, Worker};
+///
+/// let collector = Collector::new();
+/// let worker = Worker::new();
+/// let stealer = worker.stealer();
+/// let shield = collector.thin_shield();
+///
+/// worker.push(1, &shield);
+/// worker.push(2, &shield);
+///
+/// assert_eq!(stealer.steal(&shield), Some(1));
+/// assert_eq!(worker.pop(&shield), Some(2));
+/// assert_eq!(worker.pop(&shield), None);
+/// ```
+///
+/// [`Stealer`]: struct.Stealer.html
+pub struct Worker<T> {
+    inner: Arc<Inner<T>>,
+
+    /// Only one thread may operate on the back of the deque.
+    _m0: PhantomData<*mut ()>,
+}
+
+unsafe impl<T: Send> Send for Worker<T> {}
+
+impl<T> Worker<T> {
+    /// Creates a new empty deque.
+    pub fn new() -> Self {
+        let inner = Inner {
+            front: CachePadded::new(AtomicIsize::new(0)),
+            back: CachePadded::new(AtomicIsize::new(0)),
+            buffer: CachePadded::new(unsafe {
+                Atomic::from_raw(Buffer::<T>::alloc(MIN_CAP) as usize)
+            }),
+            _m0: PhantomData,
+        };
+
+        Self {
+            inner: Arc::new(inner),
+            _m0: PhantomData,
+        }
+    }
+
+    /// Creates a stealer that takes values from the front of this deque.
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+
+    /// Pushes a value onto the back of the deque.
+    pub fn push<'collector, S>(&self, value: T, shield: &S)
+    where
+        S: Shield<'collector>,
+        T: 'collector,
+    {
+        let back = self.inner.back.load(Ordering::Relaxed);
+        let front = self.inner.front.load(Ordering::Acquire);
+        let mut buffer = self.inner.buffer.load(Ordering::Relaxed, shield);
+        let cap = unsafe { buffer.as_ref_unchecked().cap };
+
+        if back - front >= cap as isize {
+            buffer = self.grow(front, back, cap * 2, shield);
+        }
+
+        unsafe {
+            buffer.as_ref_unchecked().write(back, value);
+        }
+
+        // the value must be visible before stealers can observe the new back index
+        fence(Ordering::Release);
+        self.inner.back.store(back + 1, Ordering::Relaxed);
+    }
+
+    /// Removes the value at the back of the deque.
+    pub fn pop<'collector, S>(&self, shield: &S) -> Option<T>
+    where
+        S: Shield<'collector>,
+    {
+        let back = self.inner.back.load(Ordering::Relaxed);
+        let front = self.inner.front.load(Ordering::Relaxed);
+
+        if back - front <= 0 {
+            return None;
+        }
+
+        // reserve the last value before checking if a stealer got to it first
+        let back = back - 1;
+        self.inner.back.store(back, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        let front = self.inner.front.load(Ordering::Relaxed);
+        let len = back - front;
+
+        if len < 0 {
+            self.inner.back.store(back + 1, Ordering::Relaxed);
+            return None;
+        }
+
+        let buffer = self.inner.buffer.load(Ordering::Relaxed, shield);
+        let mut value = Some(unsafe { buffer.as_ref_unchecked().read(back) });
+
+        if len == 0 {
+            // this is the last value so we have to race the stealers for it
+            if self
+                .inner
+                .front
+                .compare_exchange(front, front + 1, Ordering::SeqCst, Ordering::Relaxed)
+                .is_err()
+            {
+                mem::forget(value.take());
+            }
+
+            self.inner.back.store(back + 1, Ordering::Relaxed);
+        }
+
+        value
+    }
+
+    /// Returns true if the deque contains no elements.
+    pub fn is_empty(&self) -> bool {
+        let back = self.inner.back.load(Ordering::Relaxed);
+        let front = self.inner.front.load(Ordering::SeqCst);
+        back - front <= 0
+    }
+
+    /// Moves the values into a new buffer with the given capacity and retires the old one.
+    fn grow<'collector, 'shield, S>(
+        &self,
+        front: isize,
+        back: isize,
+        cap: usize,
+        shield: &'shield S,
+    ) -> Shared<'shield, Buffer<T>>
+    where
+        S: Shield<'collector>,
+        T: 'collector,
+    {
+        let old = self.inner.buffer.load(Ordering::Relaxed, shield);
+        let new = unsafe { Shared::from_ptr(Buffer::alloc(cap)) };
+
+        unsafe {
+            let old_ref = old.as_ref_unchecked();
+            let new_ref = new.as_ref_unchecked();
+
+            for index in front..back {
+                ptr::copy_nonoverlapping(old_ref.at(index), new_ref.at(index), 1);
+            }
+        }
+
+        self.inner.buffer.store(new, Ordering::Release);
+
+        // stealers may still be reading from the old buffer so it can't be freed right away
+        let ptr = old.as_ptr();
+        shield.retire(move || unsafe { Buffer::destroy(ptr) });
+        new
+    }
+}
+
+impl<T> Default for Worker<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Worker<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Worker { .. }")
+    }
+}
+
+/// The stealing side of a work-stealing deque created with [`Worker::stealer`].
+///
+/// Stealers take values from the front of the deque in FIFO order.
+/// They can be cloned and shared freely between threads.
+///
+/// [`Worker::stealer`]: struct.Worker.html#method.stealer
+pub struct Stealer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Stealer<T> {
+    /// Removes the value at the front of the deque.
+    /// Returns `None` if the deque is empty.
+    pub fn steal<'collector, S>(&self, shield: &S) -> Option<T>
+    where
+        S: Shield<'collector>,
+    {
+        let backoff = Backoff::new();
+
+        loop {
+            let front = self.inner.front.load(Ordering::Acquire);
+            fence(Ordering::SeqCst);
+            let back = self.inner.back.load(Ordering::Acquire);
+
+            if back - front <= 0 {
+                return None;
+            }
+
+            let buffer = self.inner.buffer.load(Ordering::Acquire, shield);
+            let value = unsafe { buffer.as_ref_unchecked().read(front) };
+
+            // if the buffer was replaced the value we read may be stale
+            if self.inner.buffer.load(Ordering::Acquire, shield) != buffer {
+                mem::forget(value);
+                continue;
+            }
+
+            if self
+                .inner
+                .front
+                .compare_exchange(front, front + 1, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+            {
+                return Some(value);
+            }
+
+            // another stealer or the worker took the value
+            mem::forget(value);
+            backoff.spin();
+        }
+    }
+
+    /// Returns true if the deque contains no elements.
+    pub fn is_empty(&self) -> bool {
+        let front = self.inner.front.load(Ordering::Acquire);
+        fence(Ordering::SeqCst);
+        let back = self.inner.back.load(Ordering::Acquire);
+        back - front <= 0
+    }
+}
+
+impl<T> Clone for Stealer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Stealer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Stealer { .. }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Worker;
+    use crate::Collector;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn push_pop_steal() {
+        let collector = Collector::new();
+        let worker = Worker::new();
+        let stealer = worker.stealer();
+        let shield = collector.thin_shield();
+
+        // enough values to grow the buffer a couple of times
+        for i in 0..1000 {
+            worker.push(i, &shield);
+        }
+
+        for i in 0..500 {
+            assert_eq!(stealer.steal(&shield), Some(i));
+        }
+
+        for i in (500..1000).rev() {
+            assert_eq!(worker.pop(&shield), Some(i));
+        }
+
+        assert!(worker.is_empty());
+        assert_eq!(stealer.steal(&shield), None);
+        assert_eq!(worker.pop(&shield), None);
+    }
+
+    #[test]
+    fn concurrent_steal() {
+        const STEALERS: usize = 4;
+        #[cfg(not(miri))]
+        const ITER: usize = 10000;
+        #[cfg(miri)]
+        const ITER: usize = 200;
+
+        let collector = Arc::new(Collector::new());
+        let worker = Worker::new();
+        let taken = Arc::new(AtomicUsize::new(0));
+        let mut handles = Vec::new();
+
+        for _ in 0..STEALERS {
+            let collector = Arc::clone(&collector);
+            let stealer = worker.stealer();
+            let taken = Arc::clone(&taken);
+
+            handles.push(thread::spawn(move || {
+                let mut sum = 0;
+
+                while taken.load(Ordering::Relaxed) < ITER {
+                    if let Some(value) = stealer.steal(&collector.thin_shield()) {
+                        sum += value;
+                        taken.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+
+                sum
+            }));
+        }
+
+        let mut sum = 0;
+
+        for i in 0..ITER {
+            let shield = collector.thin_shield();
+            worker.push(i, &shield);
+
+            if i % 3 == 0 {
+                if let Some(value) = worker.pop(&shield) {
+                    sum += value;
+                    taken.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        sum += handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .sum::<usize>();
+        assert_eq!(sum, (0..ITER).sum::<usize>());
+    }
+
+    #[test]
+    fn drop_remaining() {
+        let collector = Collector::new();
+        let worker = Worker::new();
+        let value = Arc::new(());
+
+        for _ in 0..100 {
+            worker.push(Arc::clone(&value), &collector.thin_shield());
+        }
+
+        drop(worker.pop(&collector.thin_shield()));
+        drop(worker);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,45 @@
+use super::global::Global;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// A thread that periodically advances the global epoch and executes retired functions.
+/// The thread is stopped and joined when this handle is dropped.
+pub(crate) struct Background {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Background {
+    pub(crate) fn spawn(global: Arc<Global>, interval: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let handle = thread::Builder::new()
+            .name("flize-collector".to_string())
+            .spawn(move || {
+                while !thread_stop.load(Ordering::Acquire) {
+                    let _ = Global::try_collect_light(&global);
+                    thread::park_timeout(interval);
+                }
+            })
+            .expect("failed to spawn the background collector thread");
+
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Background {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
@@ -27,16 +27,23 @@ impl Bag {
         self.deferred.is_empty()
     }
 
-    pub fn try_process(&mut self, current_epoch: Epoch) {
+    /// Executes the deferred functions that are safe to execute in the given epoch
+    /// with the given grace period. Returns how many were executed.
+    pub fn try_process(&mut self, current_epoch: Epoch, grace_period: u64) -> usize {
+        let mut executed = 0;
+
         while !self.deferred.is_empty() {
             let bottom_epoch = unsafe { self.deferred.get_unchecked(0).1 };
 
-            if bottom_epoch.two_passed(current_epoch) {
+            if bottom_epoch.grace_period_passed(current_epoch, grace_period) {
                 self.deferred.remove(0).0.call();
+                executed += 1;
             } else {
                 break;
             }
         }
+
+        executed
     }
 
     fn last_epoch(&self) -> Epoch {
@@ -0,0 +1,101 @@
+#[cfg(feature = "std")]
+use super::background::Background;
+use super::{epoch::Epoch, global::Global, Collector, CollectorObserver};
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+/// Configures and creates a `Collector`.
+///
+/// Every option can be combined with the others, the dedicated constructors on `Collector`
+/// are shorthands for setting a single option and leaving the rest at their defaults.
+///
+/// # Examples
+/// ```
+/// use flize::{Collector, CollectorObserver};
+/// use std::time::Duration;
+///
+/// struct Observer;
+///
+/// impl CollectorObserver for Observer {}
+///
+/// let collector = Collector::builder()
+///     .observer(Observer)
+///     .grace_period(3)
+///     .background_thread(Duration::from_millis(10))
+///     .build();
+///
+/// assert_eq!(collector.grace_period(), 3);
+/// ```
+pub struct CollectorBuilder {
+    observer: Option<Box<dyn CollectorObserver>>,
+    grace_period: u64,
+    #[cfg(feature = "std")]
+    background_interval: Option<Duration>,
+}
+
+impl CollectorBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            observer: None,
+            grace_period: Epoch::MIN_GRACE_PERIOD,
+            #[cfg(feature = "std")]
+            background_interval: None,
+        }
+    }
+
+    /// Reports reclamation events to `observer`, see `Collector::with_observer`.
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: CollectorObserver + 'static,
+    {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Sets how many times the global epoch has to advance before a retired function is executed,
+    /// see `Collector::with_grace_period`.
+    ///
+    /// # Panics
+    /// Panics if `grace_period` is less than two, a shorter grace period would be unsound.
+    pub fn grace_period(mut self, grace_period: usize) -> Self {
+        let grace_period = grace_period as u64;
+        assert!(
+            grace_period >= Epoch::MIN_GRACE_PERIOD,
+            "grace period must be at least two epoch advances"
+        );
+
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Collects garbage on a dedicated background thread every `interval`,
+    /// see `Collector::with_background_thread`.
+    #[cfg(feature = "std")]
+    pub fn background_thread(mut self, interval: Duration) -> Self {
+        self.background_interval = Some(interval);
+        self
+    }
+
+    pub fn build(self) -> Collector {
+        let global = Arc::new(Global::new(self.observer, self.grace_period));
+
+        #[cfg(feature = "std")]
+        let background = self.background_interval.map(|interval| {
+            global.disable_inline_collection();
+            Background::spawn(Arc::clone(&global), interval)
+        });
+
+        Collector {
+            global,
+            #[cfg(feature = "std")]
+            background,
+        }
+    }
+}
+
+impl Default for CollectorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
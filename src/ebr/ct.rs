@@ -28,6 +28,11 @@ impl CrossThread {
         self.epoch.load(Ordering::Relaxed)
     }
 
+    /// Returns the number of live full shields.
+    pub(crate) fn shield_count(&self) -> usize {
+        self.shields.load(Ordering::Relaxed).max(0) as usize
+    }
+
     unsafe fn should_advance(&self, global: &Global) -> bool {
         global.should_advance()
     }
@@ -63,12 +68,24 @@ impl CrossThread {
         }
     }
 
-    pub(crate) fn retire(&self, deferred: Deferred, epoch: Epoch) -> Option<SealedBag> {
+    pub(crate) fn retire(
+        &self,
+        deferred: Deferred,
+        epoch: Epoch,
+        global: &Global,
+    ) -> Option<SealedBag> {
         let mut bag = self.bag.lock();
-        bag.try_process(epoch);
+        let executed = if global.collects_inline() {
+            bag.try_process(epoch, global.grace_period())
+        } else {
+            0
+        };
+
         bag.push(deferred, epoch);
+        global.notify_retire();
+        global.notify_reclaim(executed);
 
-        if bag.is_full() {
+        if bag.is_full() || cfg!(feature = "eager-reclaim") {
             Some(Self::i_flush(&mut bag))
         } else {
             None
@@ -9,6 +9,9 @@ pub struct Epoch {
 
 impl Epoch {
     pub const AMOUNT: u64 = 3;
+
+    /// The smallest number of epoch advances after which a function retired in an epoch is safe to execute.
+    pub const MIN_GRACE_PERIOD: u64 = 2;
     pub const ZERO: Self = Self::from_raw(0);
 
     const fn from_raw(data: u64) -> Self {
@@ -31,13 +34,20 @@ impl Epoch {
         Self::from_raw(self.data & PIN_MASK)
     }
 
+    /// Returns the epoch following this one.
+    ///
+    /// Epochs are never wrapped around. The counter has 63 bits available which would take
+    /// centuries to exhaust even when advancing billions of times per second so all comparisons
+    /// can use plain integer arithmetic.
     pub fn next(self) -> Self {
         debug_assert!(!self.is_pinned());
+        debug_assert!(self.data < PIN_MASK, "epoch counter overflowed");
         Self::from_raw(self.data + 1)
     }
 
-    pub fn two_passed(self, now: Epoch) -> bool {
-        now.data.saturating_sub(self.data) >= 2
+    /// Returns true if the epoch has advanced at least `grace_period` times between `self` and `now`.
+    pub fn grace_period_passed(self, now: Epoch, grace_period: u64) -> bool {
+        now.data.saturating_sub(self.data) >= grace_period
     }
 
     fn unique(self) -> u64 {
@@ -106,7 +116,7 @@ impl AtomicEpoch {
 
 #[cfg(test)]
 mod tests {
-    use super::Epoch;
+    use super::{Epoch, PIN_MASK};
 
     #[test]
     fn pin_check() {
@@ -117,4 +127,25 @@ mod tests {
         let unpinned_next = epoch.unpinned().next();
         assert!(!unpinned_next.is_pinned());
     }
+
+    #[test]
+    fn near_max() {
+        let start = Epoch::from_raw(PIN_MASK - 2);
+        let end = start.next().next();
+        assert!(!end.is_pinned());
+        assert!(start.grace_period_passed(end, Epoch::MIN_GRACE_PERIOD));
+        assert!(!start
+            .next()
+            .grace_period_passed(end, Epoch::MIN_GRACE_PERIOD));
+        assert!(!start.grace_period_passed(end, 3));
+        assert!(end.pinned().is_pinned());
+        assert_eq!(end.pinned().unpinned().into_raw(), PIN_MASK);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "epoch counter overflowed")]
+    fn overflow() {
+        Epoch::from_raw(PIN_MASK).next();
+    }
 }
@@ -4,10 +4,12 @@ use super::{
     epoch::{AtomicEpoch, Epoch},
     local::{Local, LocalState},
     shield::{FullShield, Shield, ThinShield},
+    stats::{CollectorObserver, ThreadStats},
     DefinitiveEpoch,
 };
 use crate::{barrier::strong_barrier, queue::Queue, tls2::ThreadLocal, CachePadded};
-use core::sync::atomic::{fence, AtomicIsize, Ordering};
+use core::iter;
+use core::sync::atomic::{fence, AtomicBool, AtomicIsize, Ordering};
 use std::sync::Arc;
 
 pub(crate) struct Global {
@@ -15,25 +17,57 @@ pub(crate) struct Global {
     deferred: Queue<SealedBag>,
     global_epoch: CachePadded<AtomicEpoch>,
     deferred_amount: CachePadded<AtomicIsize>,
+    inline_collection: AtomicBool,
+    grace_period: u64,
+    observer: Option<Box<dyn CollectorObserver>>,
     pub(crate) ct: CrossThread,
 }
 
 impl Global {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(observer: Option<Box<dyn CollectorObserver>>, grace_period: u64) -> Self {
+        debug_assert!(grace_period >= Epoch::MIN_GRACE_PERIOD);
+
         Self {
             threads: ThreadLocal::new(),
             deferred: Queue::new(),
             global_epoch: CachePadded::new(AtomicEpoch::new(Epoch::ZERO)),
             deferred_amount: CachePadded::new(AtomicIsize::new(0)),
+            inline_collection: AtomicBool::new(true),
+            grace_period,
+            observer,
             ct: CrossThread::new(),
         }
     }
 
+    pub(crate) fn notify_retire(&self) {
+        if let Some(observer) = &self.observer {
+            observer.on_retire(1);
+        }
+    }
+
+    pub(crate) fn notify_reclaim(&self, count: usize) {
+        if let Some(observer) = &self.observer {
+            if count != 0 {
+                observer.on_reclaim(count);
+            }
+        }
+    }
+
     pub(crate) fn local_state<'a>(this: &'a Arc<Self>) -> &'a Arc<LocalState> {
         this.threads
             .get(|| Arc::new(LocalState::new(Arc::clone(this))))
     }
 
+    pub(crate) fn try_local_state(this: &Arc<Self>) -> Option<&Arc<LocalState>> {
+        this.threads
+            .try_get(|| Arc::new(LocalState::new(Arc::clone(this))))
+    }
+
+    /// Returns the state of the current thread if it has been registered, without registering it.
+    pub(crate) fn existing_local_state(&self) -> Option<&Arc<LocalState>> {
+        self.threads.get_existing()
+    }
+
     pub(crate) fn thin_shield<'a>(this: &'a Arc<Self>) -> ThinShield<'a> {
         let local_state = Self::local_state(this);
         local_state.thin_shield()
@@ -60,6 +94,37 @@ impl Global {
         DefinitiveEpoch::from(self.global_epoch.load(Ordering::SeqCst))
     }
 
+    pub(crate) fn thread_stats(&self) -> Vec<ThreadStats> {
+        let global_epoch = self.definitive_epoch();
+
+        self.threads
+            .iter()
+            .map(|state| state.stats(global_epoch))
+            .collect()
+    }
+
+    pub(crate) fn active_pins(&self) -> usize {
+        let pinned_threads = self
+            .threads
+            .iter()
+            .filter(|state| state.is_pinned())
+            .count();
+
+        pinned_threads + self.ct.shield_count()
+    }
+
+    pub(crate) fn oldest_pinned_epoch(&self) -> Option<DefinitiveEpoch> {
+        let ct_epoch = self.ct.load_epoch_relaxed();
+
+        self.threads
+            .iter()
+            .map(|state| state.load_epoch_relaxed())
+            .chain(iter::once(ct_epoch))
+            .filter(|epoch| epoch.is_pinned())
+            .map(|epoch| DefinitiveEpoch::from(epoch.unpinned()))
+            .min()
+    }
+
     pub(crate) fn retire_bag<'a, S>(&self, bag: SealedBag, _shield: &S)
     where
         S: Shield<'a>,
@@ -70,8 +135,24 @@ impl Global {
         self.deferred_amount.fetch_add(diff, Ordering::Relaxed);
     }
 
+    /// Stops threads from executing retired functions as part of regular operations.
+    /// This is used when collection is performed by a background thread instead.
+    pub(crate) fn disable_inline_collection(&self) {
+        self.inline_collection.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns true if threads should execute retired functions in their bags as they retire new ones.
+    pub(crate) fn collects_inline(&self) -> bool {
+        self.inline_collection.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many times the epoch has to advance before a retired function may be executed.
+    pub(crate) fn grace_period(&self) -> u64 {
+        self.grace_period
+    }
+
     pub(crate) fn should_advance(&self) -> bool {
-        self.deferred_amount.load(Ordering::Relaxed) > 0
+        self.collects_inline() && self.deferred_amount.load(Ordering::Relaxed) > 0
     }
 
     pub(crate) fn try_collect_light(this: &Arc<Self>) -> Result<usize, ()> {
@@ -83,7 +164,9 @@ impl Global {
         if let Ok(epoch) = self.try_advance() {
             let shield = local_state.thin_shield();
             fence(Ordering::SeqCst);
-            unsafe { Ok(self.internal_collect(epoch, &shield)) }
+            let executed = unsafe { self.internal_collect(epoch, &shield) };
+            local_state.record_reclaimed(executed);
+            Ok(executed)
         } else {
             Err(())
         }
@@ -93,7 +176,7 @@ impl Global {
         let mut executed_amount = 0;
 
         while let Some(sealed) = self.deferred.pop() {
-            if sealed.epoch().two_passed(epoch) {
+            if sealed.epoch().grace_period_passed(epoch, self.grace_period) {
                 executed_amount += sealed.run();
             } else {
                 self.deferred.push(sealed);
@@ -104,6 +187,27 @@ impl Global {
         executed_amount
     }
 
+    /// Drops the thread local states held by the global state if nothing else references them.
+    /// This breaks the reference cycle between the two so that the global state
+    /// and any garbage left in it can be dropped along with the `Collector`.
+    ///
+    /// # Safety
+    /// This may only be called when the `Collector` owning this global state is being dropped.
+    pub(crate) unsafe fn teardown(&self) {
+        // a `Local` handle may outlive the collector and is free to keep using its state,
+        // in that case we can't remove the states from the table without breaking reclamation
+        if !self
+            .threads
+            .iter()
+            .all(|state| Arc::strong_count(state) == 1)
+        {
+            return;
+        }
+
+        debug_assert!(self.threads.iter().all(|state| !state.is_pinned()));
+        self.threads.clear();
+    }
+
     fn try_advance(&self) -> Result<Epoch, ()> {
         let global_epoch = self.global_epoch.load(Ordering::Relaxed);
         let snapshot = self.threads.snapshot();
@@ -119,9 +223,33 @@ impl Global {
             .all(|epoch| epoch.unpinned() == global_epoch);
 
         if synced_epochs && ct_is_sync && !self.threads.changed_since(snapshot) {
-            self.global_epoch.try_advance(global_epoch)
+            let epoch = self.global_epoch.try_advance(global_epoch)?;
+
+            if let Some(observer) = &self.observer {
+                observer.on_epoch_advance(DefinitiveEpoch::from(epoch));
+            }
+
+            Ok(epoch)
         } else {
             Err(())
         }
     }
 }
+
+unsafe impl Send for Global {}
+unsafe impl Sync for Global {}
+
+impl Drop for Global {
+    fn drop(&mut self) {
+        // nothing can reference the global state anymore so all garbage is safe to destroy
+        unsafe {
+            if let Some(sealed) = self.ct.flush() {
+                self.notify_reclaim(sealed.run());
+            }
+
+            while let Some(sealed) = self.deferred.pop() {
+                self.notify_reclaim(sealed.run());
+            }
+        }
+    }
+}
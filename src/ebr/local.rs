@@ -3,10 +3,17 @@ use super::{
     epoch::{AtomicEpoch, Epoch},
     global::Global,
     shield::{Shield, ThinShield},
-    ADVANCE_PROBABILITY,
+    stats::ThreadStats,
+    DefinitiveEpoch, ADVANCE_PROBABILITY,
 };
 use crate::{barrier::light_barrier, deferred::Deferred, CachePadded};
-use core::{cell::UnsafeCell, fmt, marker::PhantomData, mem, sync::atomic::Ordering};
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    marker::PhantomData,
+    mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 use std::sync::Arc;
 
 pub(crate) struct LocalState {
@@ -15,6 +22,15 @@ pub(crate) struct LocalState {
     shields: UnsafeCell<usize>,
     advance_counter: UnsafeCell<usize>,
     bag: UnsafeCell<Bag>,
+    retired: AtomicUsize,
+    reclaimed: AtomicUsize,
+}
+
+/// Adds to a statistics counter.
+/// Counters are only written by the owning thread so a read-modify-write operation isn't needed.
+fn bump(counter: &AtomicUsize, amount: usize) {
+    let value = counter.load(Ordering::Relaxed);
+    counter.store(value + amount, Ordering::Relaxed);
 }
 
 impl LocalState {
@@ -25,6 +41,8 @@ impl LocalState {
             shields: UnsafeCell::new(0),
             advance_counter: UnsafeCell::new(0),
             bag: UnsafeCell::new(Bag::new()),
+            retired: AtomicUsize::new(0),
+            reclaimed: AtomicUsize::new(0),
         }
     }
 
@@ -39,6 +57,10 @@ impl LocalState {
     /// This modifies internal state.
     /// It may only be called from the thread owning this `LocalState` instance.
     unsafe fn should_advance(&self) -> bool {
+        if cfg!(feature = "eager-reclaim") {
+            return self.global.should_advance();
+        }
+
         let advance_counter = &mut *self.advance_counter.get();
         *advance_counter += 1;
 
@@ -103,7 +125,7 @@ impl LocalState {
         }
     }
 
-    fn is_pinned(&self) -> bool {
+    pub(crate) fn is_pinned(&self) -> bool {
         self.epoch.load(Ordering::Relaxed).is_pinned()
     }
 
@@ -113,21 +135,39 @@ impl LocalState {
     {
         let epoch = self.global.load_epoch_relaxed();
         let bag = unsafe { &mut *self.bag.get() };
-        bag.try_process(epoch);
+        let executed = if self.global.collects_inline() {
+            bag.try_process(epoch, self.global.grace_period())
+        } else {
+            0
+        };
+
         bag.push(deferred, epoch);
+        bump(&self.retired, 1);
+        self.global.notify_retire();
+        self.record_reclaimed(executed);
 
-        if bag.is_full() {
+        if bag.is_full() || cfg!(feature = "eager-reclaim") {
             self.force_flush(shield);
         }
     }
 
+    /// Executes the functions in the bag of this thread that are safe to execute in the current epoch.
+    /// This may only be called from the thread owning this `LocalState` instance.
+    pub(crate) fn reclaim_local(&self) -> usize {
+        let epoch = self.global.load_epoch_relaxed();
+        let bag = unsafe { &mut *self.bag.get() };
+        let executed = bag.try_process(epoch, self.global.grace_period());
+        self.record_reclaimed(executed);
+        executed
+    }
+
     pub(crate) fn flush<'a, S>(&self, shield: &S)
     where
         S: Shield<'a>,
     {
         let bag = unsafe { &mut *self.bag.get() };
 
-        if !bag.is_full() {
+        if !bag.is_empty() {
             self.force_flush(shield);
         }
     }
@@ -141,6 +181,32 @@ impl LocalState {
         self.global.retire_bag(sealed, shield);
     }
 
+    /// Records that this thread executed a number of retired functions.
+    /// This may only be called from the thread owning this `LocalState` instance.
+    pub(crate) fn record_reclaimed(&self, amount: usize) {
+        bump(&self.reclaimed, amount);
+        self.global.notify_reclaim(amount);
+    }
+
+    /// Takes a snapshot of the statistics of this thread.
+    /// This may be called from any thread.
+    pub(crate) fn stats(&self, global_epoch: DefinitiveEpoch) -> ThreadStats {
+        let epoch = self.load_epoch_relaxed();
+
+        let pinned_epoch = if epoch.is_pinned() {
+            Some(DefinitiveEpoch::from(epoch.unpinned()))
+        } else {
+            None
+        };
+
+        ThreadStats {
+            pinned_epoch,
+            epoch_lag: pinned_epoch.map_or(0, |pinned| global_epoch.0.saturating_sub(pinned.0)),
+            retired: self.retired.load(Ordering::Relaxed),
+            reclaimed: self.reclaimed.load(Ordering::Relaxed),
+        }
+    }
+
     pub(crate) fn thin_shield(&self) -> ThinShield<'_> {
         // we're creating a thin shield object so therefore we must record the creation of it
         unsafe {
@@ -151,6 +217,19 @@ impl LocalState {
     }
 }
 
+impl Drop for LocalState {
+    fn drop(&mut self) {
+        // hand our garbage over to the global state so that it isn't lost
+        let bag = unsafe { &mut *self.bag.get() };
+
+        if !bag.is_empty() {
+            let sealed = mem::replace(bag, Bag::new()).seal();
+            self.global
+                .retire_bag(sealed, unsafe { crate::unprotected() });
+        }
+    }
+}
+
 unsafe impl Send for LocalState {}
 unsafe impl Sync for LocalState {}
 
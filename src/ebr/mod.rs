@@ -1,17 +1,31 @@
+#[cfg(feature = "std")]
+mod background;
 mod bag;
+mod builder;
 mod ct;
 mod epoch;
 mod global;
 mod local;
 mod shield;
+mod stats;
 
+pub use builder::CollectorBuilder;
 pub use epoch::DefinitiveEpoch;
 pub use local::Local;
-pub use shield::{unprotected, CowShield, FullShield, Shield, ThinShield, UnprotectedShield};
+pub use shield::{
+    unprotected, unprotected_scope, CowShield, FullShield, Shield, ThinShield, UnprotectedShield,
+};
+pub use stats::{CollectorObserver, ThreadStats};
 
+use crate::Backoff;
+#[cfg(feature = "std")]
+use background::Background;
 use core::fmt;
+use epoch::Epoch;
 use global::Global;
 use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::time::Duration;
 
 const ADVANCE_PROBABILITY: usize = 128;
 
@@ -20,15 +34,64 @@ const ADVANCE_PROBABILITY: usize = 128;
 /// thread specific as of now but cross-thread participants may be added in the future. This information can be used to determine approximately
 /// when a participant last was in in a critical section and relevant shield history. The collector
 /// uses this information to determine when it is safe to execute a retired function.
+///
+/// A thread is registered with the collector the first time it creates a shield or a `Local`
+/// and its slot is recycled for a later thread once it exits, so the per-thread state
+/// is bounded by the number of threads alive at the same time rather than the number ever spawned.
 pub struct Collector {
     global: Arc<Global>,
+    #[cfg(feature = "std")]
+    background: Option<Background>,
 }
 
 impl Collector {
     pub fn new() -> Self {
-        Self {
-            global: Arc::new(Global::new()),
-        }
+        Self::builder().build()
+    }
+
+    /// Returns a builder for configuring a collector with any combination of options.
+    pub fn builder() -> CollectorBuilder {
+        CollectorBuilder::new()
+    }
+
+    /// Creates a collector that reports reclamation events to `observer`.
+    ///
+    /// All other options are left at their defaults, use `Collector::builder` to combine them.
+    pub fn with_observer<O>(observer: O) -> Self
+    where
+        O: CollectorObserver + 'static,
+    {
+        Self::builder().observer(observer).build()
+    }
+
+    /// Creates a collector that waits for the global epoch to advance `grace_period` times
+    /// before executing a retired function instead of the default two.
+    ///
+    /// A longer grace period is never required for soundness, it only delays reclamation
+    /// and increases memory usage. It can be useful for RCU-like structures that want
+    /// `Collector::wait_grace_period` to span several reader generations.
+    /// All other options are left at their defaults, use `Collector::builder` to combine them.
+    ///
+    /// # Panics
+    /// Panics if `grace_period` is less than two, a shorter grace period would be unsound.
+    pub fn with_grace_period(grace_period: usize) -> Self {
+        Self::builder().grace_period(grace_period).build()
+    }
+
+    /// Creates a collector that advances the epoch and executes retired functions
+    /// on a dedicated background thread every `interval`.
+    ///
+    /// Threads using the collector never execute retired functions themselves
+    /// which removes the cost of running destructors from their critical paths.
+    /// Functions only become visible to the background thread once the bag they are in is full or flushed.
+    /// Calling `Collector::try_collect_light` still collects garbage on the calling thread.
+    ///
+    /// The background thread is stopped and joined when the collector is dropped.
+    /// Any garbage left at that point is executed by the thread dropping the collector.
+    /// All other options are left at their defaults, use `Collector::builder` to combine them.
+    #[cfg(feature = "std")]
+    pub fn with_background_thread(interval: Duration) -> Self {
+        Self::builder().background_thread(interval).build()
     }
 
     pub fn epoch(&self) -> DefinitiveEpoch {
@@ -36,19 +99,159 @@ impl Collector {
     }
 
     /// Creates a shield on the appropriate local given the current thread.
+    ///
+    /// # Panics
+    /// Panics if more than 1024 threads are alive at the same time, see `Collector::try_thin_shield`.
     pub fn thin_shield(&self) -> ThinShield<'_> {
         Global::thin_shield(&self.global)
     }
 
+    /// Creates a shield on the appropriate local given the current thread
+    /// or returns `None` if the current thread can't be registered with the collector.
+    ///
+    /// Registration fails when more than 1024 threads are alive at the same time.
+    /// Thread slots are reused when threads exit and a thread that has
+    /// created a shield once will never fail afterwards. `Collector::thin_shield` panics instead.
+    pub fn try_thin_shield(&self) -> Option<ThinShield<'_>> {
+        Global::try_local_state(&self.global).map(|local_state| local_state.thin_shield())
+    }
+
+    /// Creates a shield, executes the closure with it and drops the shield afterwards.
+    /// Since the return value can't borrow from the shield no `Shared` can escape the closure.
+    ///
+    /// # Examples
+    /// ```
+    /// use flize::{Collector, Stack};
+    ///
+    /// let collector = Collector::new();
+    /// let stack = Stack::new();
+    ///
+    /// let top = collector.with_shield(|shield| {
+    ///     stack.push(5, shield);
+    ///     stack.pop(shield).map(|value| unsafe { *value.as_ref_unchecked() })
+    /// });
+    ///
+    /// assert_eq!(top, Some(5));
+    /// ```
+    pub fn with_shield<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&ThinShield<'_>) -> R,
+    {
+        let shield = self.thin_shield();
+        f(&shield)
+    }
+
     pub fn full_shield(&self) -> FullShield<'_> {
         Global::full_shield(&self.global)
     }
 
     /// Get the local for the current thread.
+    ///
+    /// A `Local` caches the lookup of the state of the current thread so creating shields
+    /// from it is cheaper than going through `Collector::thin_shield`. This is useful in tight loops.
+    /// It can't be sent to other threads. Dropping it doesn't deregister the thread,
+    /// the state stays in the collector and is reused by a later thread once this one exits.
+    ///
+    /// # Examples
+    /// ```
+    /// use flize::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let local = collector.local();
+    ///
+    /// for _ in 0..1000 {
+    ///     let shield = local.thin_shield();
+    ///     // ...
+    ///     drop(shield);
+    /// }
+    /// ```
     pub fn local(&self) -> Local {
         Global::local(&self.global)
     }
 
+    /// Takes a snapshot of the reclamation statistics of every thread that has used this collector.
+    ///
+    /// This is useful for finding threads that stay pinned in an old epoch
+    /// and therefore stop garbage from being reclaimed.
+    /// The snapshot is taken without stopping other threads so it may be slightly inconsistent.
+    pub fn thread_stats(&self) -> Vec<ThreadStats> {
+        self.global.thread_stats()
+    }
+
+    /// Returns the number of threads currently pinned through thin shields
+    /// plus the number of live full shields.
+    ///
+    /// Together with `Collector::oldest_pinned_epoch` this tells apart a reader stuck in
+    /// a critical section from collection simply not being triggered when reclamation falls behind.
+    /// Like `Collector::thread_stats` this is read without stopping other threads.
+    pub fn active_pins(&self) -> usize {
+        self.global.active_pins()
+    }
+
+    /// Returns the oldest epoch any shield is currently pinned in or `None` if nothing is pinned.
+    ///
+    /// Garbage retired in or after this epoch can't be reclaimed until that shield is dropped or repinned.
+    pub fn oldest_pinned_epoch(&self) -> Option<DefinitiveEpoch> {
+        self.global.oldest_pinned_epoch()
+    }
+
+    /// Returns the number of times the global epoch has to advance before
+    /// a function retired in the current epoch is executed.
+    ///
+    /// A thread can only be pinned in the current global epoch or the one before it
+    /// since the epoch can't advance while a thread is pinned in an older one.
+    /// Once the epoch has advanced twice past the epoch a function was retired in, every thread that could
+    /// have observed the retired object has left its critical section so the function can be executed.
+    /// Two advances is therefore the minimum and the default, see `Collector::with_grace_period`
+    /// for configuring a longer one.
+    pub fn grace_period(&self) -> usize {
+        self.global.grace_period() as usize
+    }
+
+    /// Blocks until the global epoch has advanced twice, analogous to an RCU grace period.
+    ///
+    /// After this returns every thread that was in a critical section when it was called
+    /// has left it at least once so no references obtained through shields created before the call remain.
+    /// Garbage collection is performed while waiting.
+    ///
+    /// This must not be called while the current thread holds a shield created by this collector
+    /// since the epoch can't advance until it is dropped and this will deadlock.
+    pub fn synchronize(&self) {
+        self.wait_epoch_advances(Epoch::MIN_GRACE_PERIOD);
+    }
+
+    /// Blocks until the global epoch has advanced `Collector::grace_period` times.
+    ///
+    /// Functions retired before this was called have become eligible for execution once it returns.
+    /// They are executed by the next collection that sees them, which is not necessarily this one
+    /// since they may still be sitting in the local bag of another thread.
+    /// With the default grace period this is equivalent to `Collector::synchronize`.
+    ///
+    /// This must not be called while the current thread holds a shield created by this collector
+    /// since the epoch can't advance until it is dropped and this will deadlock.
+    pub fn wait_grace_period(&self) {
+        self.wait_epoch_advances(self.global.grace_period());
+    }
+
+    fn wait_epoch_advances(&self, advances: u64) {
+        debug_assert!(
+            !self
+                .global
+                .existing_local_state()
+                .map_or(false, |state| state.is_pinned()),
+            "waiting for the epoch to advance while holding a shield"
+        );
+
+        let target = self.epoch().0 + advances;
+        let backoff = Backoff::new();
+
+        while self.epoch().0 < target {
+            if self.try_collect_light().is_err() {
+                backoff.snooze();
+            }
+        }
+    }
+
     /// Attempt to advance the epoch and collect garbage.
     /// The result represents whether or not the attempt to advance the global epoch
     /// was successful and if it was the integer is how many retired functions were executed.
@@ -63,6 +266,22 @@ impl Default for Collector {
     }
 }
 
+/// Dropping a `Collector` executes all retired functions that haven't been executed yet.
+/// If a `Local` created from the collector is still alive this can't be done safely
+/// and the retired functions are leaked instead.
+impl Drop for Collector {
+    fn drop(&mut self) {
+        // the background thread must release its handle to the global state before we can tear it down
+        #[cfg(feature = "std")]
+        drop(self.background.take());
+
+        // there can't be any shields borrowing the collector since we have exclusive access to it
+        unsafe {
+            self.global.teardown();
+        }
+    }
+}
+
 unsafe impl Send for Collector {}
 unsafe impl Sync for Collector {}
 
@@ -71,3 +290,371 @@ impl fmt::Debug for Collector {
         f.pad("Collector { .. }")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{unprotected, Collector, CollectorObserver, DefinitiveEpoch};
+    use crate::Shield;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn drop_executes_garbage() {
+        const THREADS: usize = 4;
+        #[cfg(not(miri))]
+        const ITER: usize = 1000;
+        #[cfg(miri)]
+        const ITER: usize = 50;
+
+        let collector = Arc::new(Collector::new());
+        let executed = Arc::new(AtomicUsize::new(0));
+        let mut handles = Vec::new();
+
+        for _ in 0..THREADS {
+            let collector = Arc::clone(&collector);
+            let executed = Arc::clone(&executed);
+
+            handles.push(thread::spawn(move || {
+                for i in 0..ITER {
+                    let executed = Arc::clone(&executed);
+                    let retire = move || {
+                        executed.fetch_add(1, Ordering::Relaxed);
+                    };
+
+                    if i % 2 == 0 {
+                        collector.thin_shield().retire(retire);
+                    } else {
+                        collector.full_shield().retire(retire);
+                    }
+                }
+            }));
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        drop(Arc::try_unwrap(collector).unwrap());
+        assert_eq!(executed.load(Ordering::Relaxed), THREADS * ITER);
+        assert_eq!(Arc::strong_count(&executed), 1);
+    }
+
+    #[test]
+    fn thread_stats() {
+        let collector = Collector::new();
+
+        for _ in 0..100 {
+            collector.thin_shield().retire(|| ());
+        }
+
+        let stats = collector.thread_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].retired, 100);
+        assert_eq!(stats[0].pinned_epoch, None);
+
+        for _ in 0..3 {
+            collector.try_collect_light().unwrap();
+        }
+
+        let shield = collector.thin_shield();
+        let stats = collector.thread_stats();
+        assert!(stats[0].reclaimed >= 96);
+        assert_eq!(stats[0].pinned_epoch, Some(collector.epoch()));
+        assert_eq!(stats[0].epoch_lag, 0);
+        drop(shield);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn background_thread() {
+        let collector = Collector::with_background_thread(Duration::from_millis(1));
+        let executed = Arc::new(AtomicUsize::new(0));
+        let shield = collector.thin_shield();
+
+        for _ in 0..10 {
+            let executed = Arc::clone(&executed);
+
+            shield.retire(move || {
+                executed.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+
+        shield.flush();
+        drop(shield);
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+
+        while executed.load(Ordering::Relaxed) != 10 {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "background thread didn't execute retired functions"
+            );
+
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        drop(collector);
+        assert_eq!(Arc::strong_count(&executed), 1);
+    }
+
+    #[test]
+    fn flush_makes_garbage_global() {
+        let collector = Arc::new(Collector::new());
+        let executed = Arc::new(AtomicUsize::new(0));
+
+        {
+            let executed = Arc::clone(&executed);
+            let collector = Arc::clone(&collector);
+
+            thread::spawn(move || {
+                let shield = collector.thin_shield();
+
+                for _ in 0..5 {
+                    let executed = Arc::clone(&executed);
+
+                    shield.retire(move || {
+                        executed.fetch_add(1, Ordering::Relaxed);
+                    });
+                }
+
+                // the state of this thread stays in the collector with the bag in it once the thread exits
+                shield.flush();
+            })
+            .join()
+            .unwrap();
+        }
+
+        for _ in 0..3 {
+            collector.try_collect_light().unwrap();
+        }
+
+        assert_eq!(executed.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn observer() {
+        #[derive(Default)]
+        struct Counts {
+            advanced: AtomicUsize,
+            retired: AtomicUsize,
+            reclaimed: AtomicUsize,
+        }
+
+        struct Observer(Arc<Counts>);
+
+        impl CollectorObserver for Observer {
+            fn on_epoch_advance(&self, _epoch: DefinitiveEpoch) {
+                self.0.advanced.fetch_add(1, Ordering::Relaxed);
+            }
+
+            fn on_retire(&self, count: usize) {
+                self.0.retired.fetch_add(count, Ordering::Relaxed);
+            }
+
+            fn on_reclaim(&self, count: usize) {
+                self.0.reclaimed.fetch_add(count, Ordering::Relaxed);
+            }
+        }
+
+        let counts = Arc::new(Counts::default());
+        let collector = Collector::with_observer(Observer(Arc::clone(&counts)));
+
+        for i in 0..100 {
+            if i % 2 == 0 {
+                collector.thin_shield().retire(|| ());
+            } else {
+                collector.full_shield().retire(|| ());
+            }
+        }
+
+        let _ = collector.try_collect_light();
+        assert!(counts.advanced.load(Ordering::Relaxed) > 0);
+        drop(collector);
+        assert_eq!(counts.retired.load(Ordering::Relaxed), 100);
+        assert_eq!(counts.reclaimed.load(Ordering::Relaxed), 100);
+    }
+
+    #[test]
+    fn synchronize() {
+        let collector = Arc::new(Collector::new());
+        let reader = {
+            let collector = Arc::clone(&collector);
+
+            thread::spawn(move || {
+                let shield = collector.thin_shield();
+                thread::sleep(Duration::from_millis(10));
+                drop(shield);
+            })
+        };
+
+        let start = collector.epoch();
+        collector.synchronize();
+        assert!(collector.epoch().0 >= start.0 + 2);
+        reader.join().unwrap();
+    }
+
+    #[test]
+    #[cfg(not(feature = "eager-reclaim"))]
+    fn wait_grace_period() {
+        let collector = Collector::with_grace_period(4);
+        assert_eq!(collector.grace_period(), 4);
+        let executed = Arc::new(AtomicUsize::new(0));
+
+        {
+            let executed = Arc::clone(&executed);
+            collector.thin_shield().retire(move || {
+                executed.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+
+        let start = collector.epoch();
+        collector.synchronize();
+        collector.thin_shield().flush();
+        assert_eq!(executed.load(Ordering::Relaxed), 0);
+
+        collector.wait_grace_period();
+        assert!(collector.epoch().0 >= start.0 + 4);
+        drop(collector);
+        assert_eq!(executed.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "grace period must be at least two epoch advances")]
+    fn grace_period_too_short() {
+        Collector::with_grace_period(1);
+    }
+
+    #[test]
+    fn sequential_threads_reuse_slots() {
+        #[cfg(not(miri))]
+        const THREADS: usize = 10_000;
+        #[cfg(miri)]
+        const THREADS: usize = 20;
+
+        let collector = Arc::new(Collector::new());
+
+        for _ in 0..THREADS {
+            let collector = Arc::clone(&collector);
+            thread::spawn(move || collector.thin_shield().retire(|| ()))
+                .join()
+                .unwrap();
+        }
+
+        let stats = collector.thread_stats();
+        assert!(stats.len() < THREADS / 10);
+        assert_eq!(
+            stats.iter().map(|stats| stats.retired).sum::<usize>(),
+            THREADS
+        );
+    }
+
+    #[test]
+    fn unprotected_retire_runs_immediately() {
+        let executed = AtomicUsize::new(0);
+        let shield = unsafe { unprotected() };
+
+        for i in 1..=3 {
+            shield.retire(|| {
+                executed.fetch_add(1, Ordering::Relaxed);
+            });
+
+            assert_eq!(executed.load(Ordering::Relaxed), i);
+        }
+    }
+
+    #[test]
+    fn active_pins() {
+        let collector = Collector::new();
+        assert_eq!(collector.active_pins(), 0);
+        assert_eq!(collector.oldest_pinned_epoch(), None);
+
+        let thin = collector.thin_shield();
+        let nested = collector.thin_shield();
+        let full = collector.full_shield();
+        assert_eq!(collector.active_pins(), 2);
+        assert_eq!(collector.oldest_pinned_epoch(), Some(collector.epoch()));
+
+        drop(full);
+        drop(nested);
+        assert_eq!(collector.active_pins(), 1);
+        drop(thin);
+        assert_eq!(collector.active_pins(), 0);
+        assert_eq!(collector.oldest_pinned_epoch(), None);
+    }
+
+    #[test]
+    fn try_thin_shield() {
+        let collector = Collector::new();
+        let shield = collector.try_thin_shield().unwrap();
+        shield.retire(|| ());
+        assert_eq!(collector.thread_stats()[0].retired, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn background_thread_runs_destructors() {
+        let collector = Collector::with_background_thread(Duration::from_millis(1));
+        let main_thread = thread::current().id();
+        let inline = Arc::new(AtomicUsize::new(0));
+        let executed = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..1000 {
+            let inline = Arc::clone(&inline);
+            let executed = Arc::clone(&executed);
+
+            collector.thin_shield().retire(move || {
+                if thread::current().id() == main_thread {
+                    inline.fetch_add(1, Ordering::Relaxed);
+                }
+
+                executed.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+
+        collector.thin_shield().flush();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+
+        while executed.load(Ordering::Relaxed) != 1000 {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "background thread didn't execute retired functions"
+            );
+
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(inline.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    #[cfg(not(feature = "eager-reclaim"))]
+    fn reclaim_now() {
+        let collector = Collector::new();
+        let shield = collector.thin_shield();
+
+        for _ in 0..10 {
+            shield.retire(|| ());
+        }
+
+        assert_eq!(shield.reclaim_now(), 0);
+        drop(shield);
+
+        for _ in 0..2 {
+            collector.try_collect_light().unwrap();
+        }
+
+        assert_eq!(collector.thin_shield().reclaim_now(), 10);
+    }
+
+    #[test]
+    fn drop_with_live_local() {
+        let collector = Collector::new();
+        let local = collector.local();
+        local.thin_shield().retire(|| ());
+        drop(collector);
+        drop(local.thin_shield());
+    }
+}
@@ -23,6 +23,24 @@ pub trait Shield<'a>: Clone + fmt::Debug {
     /// If this method is called from an [`unprotected`] shield, the closure will be executed
     /// immediately without unpinning the thread.
     ///
+    /// This is the intended way to perform a long blocking operation such as I/O in the middle
+    /// of a critical section without holding back the global epoch. Since the shield is borrowed mutably
+    /// no `Shared` created from it can be used inside the closure so this is entirely safe.
+    /// Pointers must be reloaded after the closure returns as the objects they pointed to may have been reclaimed.
+    ///
+    /// # Examples
+    /// ```
+    /// use flize::{Collector, Shield};
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let collector = Collector::new();
+    /// let mut shield = collector.thin_shield();
+    ///
+    /// // other threads may advance the epoch and reclaim garbage while we sleep
+    /// shield.repin_after(|| thread::sleep(Duration::from_millis(1)));
+    /// ```
+    ///
     /// [`unprotected`]: fn.unprotected.html
     fn repin_after<F, R>(&mut self, f: F) -> R
     where
@@ -31,6 +49,10 @@ pub trait Shield<'a>: Clone + fmt::Debug {
     /// Schedule a closure for execution once no shield may hold a reference
     /// to an object unlinked with the current shield.
     ///
+    /// The closure is always handed to the collector that created the shield so generic code
+    /// that only has access to a shield can retire objects without knowing the collector.
+    /// Objects must only be retired through shields of the collector protecting the structure they were unlinked from.
+    ///
     /// If this method is called from an [`unprotected`] shield, the closure will be executed
     /// immediately.
     ///
@@ -40,6 +62,11 @@ pub trait Shield<'a>: Clone + fmt::Debug {
         F: FnOnce() + 'a;
 
     /// Moves all deferred functions in the queue associated with the shield to the one associated with the collector.
+    ///
+    /// Functions retired through a `ThinShield` are buffered in a thread local bag and are normally only
+    /// made visible to other threads once the bag fills up. A thread that retires a burst of objects and then goes idle
+    /// should call this so that collections on other threads can execute them.
+    /// This does not execute any functions itself.
     fn flush(&self);
 }
 
@@ -98,7 +125,7 @@ impl<'a> Shield<'a> for FullShield<'a> {
         let epoch = self.global.load_epoch_relaxed();
         let deferred = Deferred::new(f);
 
-        if let Some(sealed) = self.global.ct.retire(deferred, epoch) {
+        if let Some(sealed) = self.global.ct.retire(deferred, epoch, self.global) {
             self.global.retire_bag(sealed, self);
         }
     }
@@ -151,6 +178,17 @@ impl<'a> ThinShield<'a> {
             _m0: PhantomData,
         }
     }
+
+    /// Executes the functions retired by the current thread that are safe to execute right now
+    /// and returns how many were executed.
+    ///
+    /// This is useful for reclaiming garbage at a convenient point instead of during later operations.
+    /// Only functions retired at least the collector's grace period ago are executed so this does nothing if
+    /// the global epoch hasn't advanced enough since they were retired.
+    /// Functions that have been flushed to the collector are not affected.
+    pub fn reclaim_now(&self) -> usize {
+        self.local_state.reclaim_local()
+    }
 }
 
 impl<'a> Shield<'a> for ThinShield<'a> {
@@ -316,6 +354,44 @@ pub unsafe fn unprotected() -> &'static UnprotectedShield {
     &UNPROTECTED
 }
 
+/// Executes a closure with a borrowed unprotected shield.
+///
+/// This is equivalent to [`unprotected`] except that the shield is passed by reference to the closure.
+/// Since the closure must accept a reference of any lifetime no [`Shared`] loaded through that reference
+/// can be returned from the closure, preventing such pointers from escaping.
+/// `UnprotectedShield` is `Copy` so the closure can still return a copy of the shield itself
+/// and anything loaded through that copy afterwards is just as unprotected as with [`unprotected`].
+///
+/// # Safety
+/// The same requirements as for [`unprotected`] apply.
+///
+/// # Examples
+/// ```
+/// use flize::{self, Atomic, Shared};
+/// use std::sync::atomic::Ordering::Relaxed;
+///
+/// let a: Atomic<i32> = Atomic::new(unsafe { Shared::from_ptr(Box::into_raw(Box::new(7))) });
+///
+/// let value = unsafe {
+///     flize::unprotected_scope(|shield| {
+///         let ptr = a.swap(Shared::null(), Relaxed, shield);
+///         *ptr.into_owned()
+///     })
+/// };
+///
+/// assert_eq!(value, 7);
+/// ```
+///
+/// [`unprotected`]: fn.unprotected.html
+/// [`Shared`]: struct.Shared.html
+pub unsafe fn unprotected_scope<F, R>(f: F) -> R
+where
+    F: for<'shield> FnOnce(&'shield UnprotectedShield) -> R,
+{
+    let shield = UnprotectedShield { _private: () };
+    f(&shield)
+}
+
 /// This is a utility type that allows you to either take a reference to a shield
 /// and be bound by the lifetime of it or take an owned shield use `'static`.
 #[derive(Clone, Debug)]
@@ -0,0 +1,59 @@
+use super::DefinitiveEpoch;
+
+/// A snapshot of the reclamation statistics of one thread participating in a `Collector`.
+///
+/// Threads are identified by a slot in the collector which is reused by a new thread
+/// once the previous one exits. The counters accumulate over all threads that have used the slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadStats {
+    /// The epoch the thread is pinned in or `None` if it has no active shields.
+    pub pinned_epoch: Option<DefinitiveEpoch>,
+
+    /// How many epochs the pinned epoch of the thread lags behind the global epoch.
+    /// This is always zero if the thread isn't pinned.
+    pub epoch_lag: u64,
+
+    /// The number of functions retired through thin shields on this thread.
+    pub retired: usize,
+
+    /// The number of retired functions executed by collections performed on this thread.
+    ///
+    /// This counts functions run from the local bag of this thread and from the collector
+    /// during global collections this thread performed, regardless of which thread retired them.
+    /// Functions retired here but executed by another thread are counted by that thread instead.
+    pub reclaimed: usize,
+}
+
+/// Receives notifications about reclamation events in a `Collector`.
+///
+/// This allows exporting collector internals to a metrics system without flize depending on one.
+/// All methods have empty default implementations so only the interesting events need to be handled.
+/// The methods are called inline on the threads performing the operations and should therefore be cheap.
+///
+/// # Examples
+/// ```
+/// use flize::{Collector, CollectorObserver, Shield};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[derive(Default)]
+/// struct Retired(AtomicUsize);
+///
+/// impl CollectorObserver for Retired {
+///     fn on_retire(&self, count: usize) {
+///         self.0.fetch_add(count, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let collector = Collector::with_observer(Retired::default());
+/// collector.thin_shield().retire(|| ());
+/// ```
+pub trait CollectorObserver: Send + Sync {
+    /// Called after the global epoch has been advanced to `epoch`.
+    fn on_epoch_advance(&self, _epoch: DefinitiveEpoch) {}
+
+    /// Called when `count` functions have been retired.
+    fn on_retire(&self, _count: usize) {}
+
+    /// Called when `count` retired functions have been executed.
+    fn on_reclaim(&self, _count: usize) {}
+}
@@ -42,26 +42,37 @@
 //! To make this possible we conditionally depend on `winapi` on Windows targets and `libc` on Linux and macOS targets.
 //! This accelerated bookkeeping is controlled by the `fast-barrier` Cargo feature.
 //! This flag is enabled by default and disabling it will cause the more general implementation to be compiled on all targets.
+//!
+//! The `eager-reclaim` Cargo feature makes every retired function visible to the collector immediately and
+//! attempts a collection every time a thread leaves a critical section. Retired objects are then destroyed
+//! as soon as it is safe which makes use-after-free bugs in code built on flize much more likely to be caught
+//! by tools like Miri and AddressSanitizer. This is intended for testing only and will severely hurt performance.
 
 mod atomic;
 mod backoff;
 mod barrier;
 mod cache_padded;
 mod deferred;
+mod deque;
 mod ebr;
 mod lazy;
+mod list;
 mod mutex;
 mod queue;
 mod shared;
+mod stack;
 mod tag;
 mod tls2;
 
 pub use atomic::Atomic;
 pub use backoff::Backoff;
 pub use cache_padded::CachePadded;
+pub use deque::{Stealer, Worker};
 pub use ebr::{
-    unprotected, Collector, CowShield, DefinitiveEpoch, FullShield, Local, Shield, ThinShield,
-    UnprotectedShield,
+    unprotected, unprotected_scope, Collector, CollectorBuilder, CollectorObserver, CowShield,
+    DefinitiveEpoch, FullShield, Local, Shield, ThinShield, ThreadStats, UnprotectedShield,
 };
+pub use list::List;
 pub use shared::Shared;
+pub use stack::Stack;
 pub use tag::{NullTag, Tag};
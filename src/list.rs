@@ -0,0 +1,358 @@
+use crate::{Atomic, Backoff, Shared, Shield, Tag};
+use core::fmt;
+use core::marker::PhantomData;
+use core::sync::atomic::Ordering;
+use generic_array::{typenum::U1, GenericArray};
+
+/// A single bit tag stored in the `next` pointer of a node.
+/// If it is set the node has been logically deleted from the list.
+#[derive(Debug, Clone, Copy)]
+struct Mark(bool);
+
+impl Tag for Mark {
+    type Size = U1;
+
+    fn deserialize(bits: GenericArray<bool, Self::Size>) -> Self {
+        Mark(bits[0])
+    }
+
+    fn serialize(self) -> GenericArray<bool, Self::Size> {
+        let mut bits = GenericArray::default();
+        bits[0] = self.0;
+        bits
+    }
+}
+
+/// A pointer to a node, marked if the node holding it is logically deleted.
+type Link<T> = Atomic<Node<T>, Mark>;
+
+/// A node in the list.
+struct Node<T> {
+    /// The value stored in this node, the list is sorted by it.
+    value: T,
+
+    /// The next node in the list, marked if this node is logically deleted.
+    next: Link<T>,
+}
+
+impl<T> Node<T> {
+    /// Allocates a new node that isn't linked to anything.
+    fn new(value: T) -> *mut Self {
+        let node = Self {
+            value,
+            next: Atomic::null(),
+        };
+
+        Box::into_raw(Box::new(node))
+    }
+
+    /// Drops the value and deallocates the node.
+    ///
+    /// # Safety
+    /// The pointer must have been created by `Node::new` and may not be used again.
+    unsafe fn destroy(this: *mut Self) {
+        drop(Box::from_raw(this));
+    }
+}
+
+/// A lock-free sorted linked list based on the algorithm described by Timothy L. Harris.
+///
+/// The list acts as a set, each value is present at most once.
+/// Removal happens in two steps. First the node is logically deleted by setting a mark in the low tag
+/// of its `next` pointer, after which no node can be inserted after it.
+/// Then it is physically unlinked, either by the remover or by any traversal that encounters it.
+/// Unlinked nodes are retired through the shield used for the operation that unlinked them.
+///
+/// # Examples
+/// ```
+/// use flize::{Collector, List};
+///
+/// let collector = Collector::new();
+/// let list = List::new();
+/// let shield = collector.thin_shield();
+///
+/// assert!(list.insert(5, &shield));
+/// assert!(!list.insert(5, &shield));
+/// assert!(list.contains(&5, &shield));
+/// assert!(list.remove(&5, &shield));
+/// assert!(!list.contains(&5, &shield));
+/// ```
+pub struct List<T> {
+    /// The first node in the list, this pointer is never marked.
+    head: Link<T>,
+
+    /// Indicates that dropping a `List<T>` may drop values of type `T`.
+    _m0: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Send + Sync> Sync for List<T> {}
+
+impl<T> List<T> {
+    /// Creates a new empty list.
+    pub fn new() -> Self {
+        Self {
+            head: Atomic::null(),
+            _m0: PhantomData,
+        }
+    }
+}
+
+impl<T> List<T>
+where
+    T: Ord,
+{
+    /// Finds the first node with a value that isn't less than `value`.
+    /// Returns that node along with the link pointing to it.
+    /// Both are unmarked at the time of observation.
+    ///
+    /// Marked nodes encountered on the way are unlinked and retired.
+    fn find<'collector, 'shield, S>(
+        &'shield self,
+        value: &T,
+        shield: &'shield S,
+    ) -> (&'shield Link<T>, Shared<'shield, Node<T>, Mark>)
+    where
+        S: Shield<'collector>,
+        T: 'collector,
+    {
+        let backoff = Backoff::new();
+
+        'retry: loop {
+            let mut prev = &self.head;
+            let mut current = prev.load(Ordering::Acquire, shield);
+
+            while let Some(node) = unsafe { current.as_ref() } {
+                let next = node.next.load(Ordering::Acquire, shield);
+
+                if next.tag_lo().0 {
+                    // the node is logically deleted so we try to help unlink it
+                    let unmarked = next.strip();
+
+                    match prev.compare_exchange(
+                        current,
+                        unmarked,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                        shield,
+                    ) {
+                        Ok(_) => {
+                            let ptr = current.as_ptr();
+                            shield.retire(move || unsafe { Node::destroy(ptr) });
+                            current = unmarked;
+                            continue;
+                        }
+
+                        // the predecessor changed or was deleted itself, start over
+                        Err(_) => {
+                            backoff.spin();
+                            continue 'retry;
+                        }
+                    }
+                }
+
+                if node.value >= *value {
+                    break;
+                }
+
+                prev = &node.next;
+                current = next;
+            }
+
+            return (prev, current);
+        }
+    }
+
+    /// Inserts a value into the list.
+    /// Returns false and drops the value if an equal value is already present.
+    pub fn insert<'collector, S>(&self, value: T, shield: &S) -> bool
+    where
+        S: Shield<'collector>,
+        T: 'collector,
+    {
+        let new = unsafe { Shared::from_ptr(Node::new(value)) };
+        let new_ref = unsafe { new.as_ref_unchecked() };
+
+        loop {
+            let (prev, current) = self.find(&new_ref.value, shield);
+
+            if let Some(node) = unsafe { current.as_ref() } {
+                if node.value == new_ref.value {
+                    // the node was never published so we can destroy it right away
+                    unsafe {
+                        Node::destroy(new.as_ptr());
+                    }
+
+                    return false;
+                }
+            }
+
+            new_ref.next.store(current, Ordering::Relaxed);
+
+            if prev
+                .compare_exchange(current, new, Ordering::Release, Ordering::Relaxed, shield)
+                .is_ok()
+            {
+                return true;
+            }
+        }
+    }
+
+    /// Returns true if the list contains a value equal to `value`.
+    pub fn contains<'collector, S>(&self, value: &T, shield: &S) -> bool
+    where
+        S: Shield<'collector>,
+    {
+        let mut current = self.head.load(Ordering::Acquire, shield);
+
+        while let Some(node) = unsafe { current.strip().as_ref() } {
+            let next = node.next.load(Ordering::Acquire, shield);
+
+            if node.value >= *value {
+                return node.value == *value && !next.tag_lo().0;
+            }
+
+            current = next;
+        }
+
+        false
+    }
+
+    /// Removes a value from the list.
+    /// Returns false if no value equal to `value` was present.
+    ///
+    /// The value is dropped once the node holding it has been reclaimed.
+    pub fn remove<'collector, S>(&self, value: &T, shield: &S) -> bool
+    where
+        S: Shield<'collector>,
+        T: 'collector,
+    {
+        loop {
+            let (prev, current) = self.find(value, shield);
+
+            let node = match unsafe { current.as_ref() } {
+                Some(node) if node.value == *value => node,
+                _ => return false,
+            };
+
+            // logically delete the node, only one remover can observe the mark unset
+            let next = node
+                .next
+                .fetch_or_tag_lo(Mark(true), Ordering::AcqRel, shield);
+
+            if next.tag_lo().0 {
+                continue;
+            }
+
+            // try to physically unlink it, if that fails a traversal will do it for us
+            if prev
+                .compare_exchange(current, next, Ordering::AcqRel, Ordering::Relaxed, shield)
+                .is_ok()
+            {
+                let ptr = current.as_ptr();
+                shield.retire(move || unsafe { Node::destroy(ptr) });
+            } else {
+                self.find(value, shield);
+            }
+
+            return true;
+        }
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        // we have exclusive access to the list so nothing can be reading the nodes
+        unsafe {
+            crate::unprotected_scope(|shield| {
+                let mut current = self.head.load(Ordering::Relaxed, shield);
+
+                while let Some(node) = current.strip().as_ref() {
+                    let next = node.next.load(Ordering::Relaxed, shield);
+                    Node::destroy(current.strip().as_ptr());
+                    current = next;
+                }
+            });
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("List { .. }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::List;
+    use crate::Collector;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn insert_remove() {
+        let collector = Collector::new();
+        let list = List::new();
+        let shield = collector.thin_shield();
+
+        for i in &[5, 1, 9, 3, 7] {
+            assert!(list.insert(*i, &shield));
+        }
+
+        assert!(!list.insert(3, &shield));
+        assert!(list.remove(&3, &shield));
+        assert!(!list.remove(&3, &shield));
+
+        for i in 0..10 {
+            let expected = [1, 5, 7, 9].contains(&i);
+            assert_eq!(list.contains(&i, &shield), expected);
+        }
+    }
+
+    #[test]
+    fn concurrent_insert_remove() {
+        const THREADS: usize = 8;
+        #[cfg(not(miri))]
+        const ITER: usize = 200;
+        #[cfg(miri)]
+        const ITER: usize = 20;
+
+        let collector = Arc::new(Collector::new());
+        let list = Arc::new(List::new());
+        let mut handles = Vec::new();
+
+        for t in 0..THREADS {
+            let collector = Arc::clone(&collector);
+            let list = Arc::clone(&list);
+
+            handles.push(thread::spawn(move || {
+                for i in 0..ITER {
+                    let shield = collector.thin_shield();
+                    let value = i * THREADS + t;
+                    assert!(list.insert(value, &shield));
+
+                    if value % 2 == 0 {
+                        assert!(list.remove(&value, &shield));
+                    }
+                }
+            }));
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let shield = collector.thin_shield();
+
+        for value in 0..THREADS * ITER {
+            assert_eq!(list.contains(&value, &shield), value % 2 == 1);
+        }
+    }
+}
@@ -365,3 +365,106 @@ impl<T> Default for Queue<T> {
         Queue::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Queue, BLOCK_CAP, LAP};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    const LENGTHS: [usize; 7] = [
+        1,
+        BLOCK_CAP - 1,
+        BLOCK_CAP,
+        BLOCK_CAP + 1,
+        LAP * 2,
+        LAP * 2 + 1,
+        1000,
+    ];
+
+    struct DropCounter<'a>(&'a AtomicUsize);
+
+    impl<'a> Drop for DropCounter<'a> {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn zero_sized_elements() {
+        let queue = Queue::new();
+        let count = LAP * 4 + 3;
+
+        for _ in 0..count {
+            queue.push(());
+        }
+
+        for _ in 0..count {
+            assert_eq!(queue.pop(), Some(()));
+        }
+
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn fifo_order() {
+        for &len in LENGTHS.iter() {
+            let queue = Queue::new();
+
+            for i in 0..len {
+                queue.push(i);
+            }
+
+            for i in 0..len {
+                assert_eq!(queue.pop(), Some(i));
+            }
+
+            assert_eq!(queue.pop(), None);
+        }
+    }
+
+    #[test]
+    fn fifo_order_producer_consumer() {
+        for &len in LENGTHS.iter() {
+            let queue = Arc::new(Queue::new());
+
+            let producer = {
+                let queue = Arc::clone(&queue);
+
+                thread::spawn(move || {
+                    for i in 0..len {
+                        queue.push(i);
+                    }
+                })
+            };
+
+            let mut expected = 0;
+
+            while expected < len {
+                if let Some(value) = queue.pop() {
+                    assert_eq!(value, expected);
+                    expected += 1;
+                }
+            }
+
+            producer.join().unwrap();
+            assert_eq!(queue.pop(), None);
+        }
+    }
+
+    #[test]
+    fn drop_remaining() {
+        let drops = AtomicUsize::new(0);
+        let queue = Queue::new();
+
+        for _ in 0..1000 {
+            queue.push(DropCounter(&drops));
+        }
+
+        drop(queue.pop());
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        drop(queue);
+        assert_eq!(drops.load(Ordering::Relaxed), 1000);
+    }
+}
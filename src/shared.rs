@@ -1,19 +1,25 @@
-use crate::tag::{read_tag, set_tag, strip, NullTag, Tag, TagPosition};
+use crate::tag::{fits_lo, read_tag, set_tag, strip, NullTag, Tag, TagPosition};
+use core::cmp::Ordering;
 use core::fmt::{self, Debug};
+use core::hash::{Hash, Hasher};
 use core::marker::PhantomData;
-use core::ptr;
 
 /// A `Shared` represents a tagged pointer.
 /// It provides various utility methods for type conversion
 /// and tag manipulation. In addition it is the only pointer type
 /// that can be used to interact with `Atomic` since this type
 /// enforces a lifetime based on the shield used to create it.
+///
+/// `Shared` is covariant in `'shield` so a pointer can be passed anywhere
+/// a pointer bound to a shorter lifetime is expected without any conversion.
+///
+/// The pointer and its tags are packed into a single word so `V` must be `Sized`.
+/// Trait objects and slices can be pointed to through an extra indirection
+/// such as `Shared<'shield, Box<dyn Trait>>`.
 #[repr(transparent)]
 pub struct Shared<'shield, V, T1 = NullTag, T2 = NullTag>
 where
     V: 'shield,
-    T1: Tag,
-    T2: Tag,
 {
     pub(crate) data: usize,
     _m0: PhantomData<&'shield ()>,
@@ -22,22 +28,15 @@ where
     _m3: PhantomData<T2>,
 }
 
+// This impl block has no trait bounds so that the constructors
+// can be `const fn` on our MSRV which allows using them in constants.
 impl<'shield, V, T1, T2> Shared<'shield, V, T1, T2>
 where
     V: 'shield,
-    T1: Tag,
-    T2: Tag,
 {
-    pub fn null() -> Self {
-        unsafe { Self::from_raw(ptr::null::<()>() as usize) }
-    }
-
-    /// Constructs a `Shared` from a raw tagged pointer with an arbitrary lifetime.
-    ///
-    /// # Safety
-    /// The alignment of `V` must free up sufficient low bits so that `T` fits.
-    pub unsafe fn from_ptr(ptr: *mut V) -> Self {
-        Self::from_raw(ptr as usize)
+    /// Constructs a null `Shared` without any tags.
+    pub const fn null() -> Self {
+        unsafe { Self::from_raw(0) }
     }
 
     /// Constructs a `Shared` from a raw tagged pointer represented as an integer with an arbitrary lifetime.
@@ -47,7 +46,7 @@ where
     /// # Safety
     /// This is marked unsafe because extreme caution must be taken to
     /// supply correct data and ensure the lifetime is what you expect.
-    pub unsafe fn from_raw(data: usize) -> Self {
+    pub const unsafe fn from_raw(data: usize) -> Self {
         Self {
             data,
             _m0: PhantomData,
@@ -56,6 +55,37 @@ where
             _m3: PhantomData,
         }
     }
+}
+
+impl<'shield, V, T1, T2> Shared<'shield, V, T1, T2>
+where
+    V: 'shield,
+    T1: Tag,
+    T2: Tag,
+{
+    /// Constructs a `Shared` from a raw tagged pointer with an arbitrary lifetime.
+    ///
+    /// # Safety
+    /// The alignment of `V` must free up sufficient low bits so that `T` fits.
+    pub unsafe fn from_ptr(ptr: *mut V) -> Self {
+        Self::from_raw(ptr as usize)
+    }
+
+    /// Constructs a `Shared` from an untagged address and the two tags with an arbitrary lifetime.
+    /// This is the inverse of `Shared::into_raw_parts`.
+    ///
+    /// # Safety
+    /// - The address must have no tag bits set.
+    /// - The pointer must be valid and protected from reclamation for the chosen lifetime.
+    pub unsafe fn from_raw_parts(addr: usize, tag_lo: T1, tag_hi: T2) -> Self {
+        debug_assert_eq!(strip::<T1, T2>(addr), addr, "address has tag bits set");
+        Self::from_raw(addr).with_tag_lo(tag_lo).with_tag_hi(tag_hi)
+    }
+
+    /// Split the tagged pointer into the untagged address and the two tags.
+    pub fn into_raw_parts(self) -> (usize, T1, T2) {
+        (self.addr(), self.tag_lo(), self.tag_hi())
+    }
 
     /// Get the raw tagged pointer as an integer.
     pub fn into_raw(self) -> usize {
@@ -63,10 +93,25 @@ where
     }
 
     /// Get the raw tagged pointer.
+    ///
+    /// Tagged pointers are stored as integers so the returned pointer is created by an
+    /// integer to pointer cast and relies on the provenance of the allocation having been exposed.
+    /// This is compatible with Miri's permissive provenance mode but not with `-Zmiri-strict-provenance`.
     pub fn as_ptr(self) -> *mut V {
         self.data as *mut V
     }
 
+    /// Get the address the pointer points to with all tags stripped.
+    /// This is the identity of the pointee and is what `Hash` and `Ord` are based on.
+    pub fn addr(self) -> usize {
+        strip::<T1, T2>(self.data)
+    }
+
+    /// Returns true if both pointers point to the same address, ignoring any tags.
+    pub fn ptr_eq(a: Self, b: Self) -> bool {
+        a.addr() == b.addr()
+    }
+
     /// Remove all tags by zeroing their bits.
     pub fn strip(self) -> Self {
         let data = strip::<T1, T2>(self.data);
@@ -117,11 +162,42 @@ where
         &mut *self.as_ptr()
     }
 
+    /// Takes ownership of the pointee by converting the pointer back into a `Box`.
+    /// Any tags are stripped before the conversion.
+    ///
+    /// # Safety
+    /// - The pointer must have been created from a `Box<V>` with `Box::into_raw`.
+    /// - The object must be unreachable from shared memory and no other shield may hold a reference to it.
+    /// - The object must not have been retired since that would cause a double free.
+    pub unsafe fn into_owned(self) -> Box<V> {
+        Box::from_raw(self.strip().as_ptr())
+    }
+
     /// Check if the tagged pointer is null.
     pub fn is_null(self) -> bool {
         self.as_ptr().is_null()
     }
 
+    /// Returns `None` if the tagged pointer is null and `Some` otherwise.
+    pub fn as_option(self) -> Option<Self> {
+        if !self.is_null() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the tagged pointer.
+    /// This will panic with the supplied message if the tagged pointer is null.
+    pub fn expect(self, msg: &str) -> Self {
+        self.as_option().expect(msg)
+    }
+
+    /// Returns the tagged pointer or `default` if it is null.
+    pub fn unwrap_or(self, default: Self) -> Self {
+        self.as_option().unwrap_or(default)
+    }
+
     /// Get the tag in the low position.
     pub fn tag_lo(self) -> T1 {
         let bits = read_tag::<T1>(self.data, TagPosition::Lo);
@@ -134,8 +210,19 @@ where
         Tag::deserialize(bits)
     }
 
+    /// Split the tagged pointer into the pointer with all tags stripped and the two tags.
+    ///
+    /// The parts can be combined again with `with_tag_lo` and `with_tag_hi`,
+    /// which overwrite any tag bits present in the pointer.
+    pub fn decompose(self) -> (Self, T1, T2) {
+        (self.strip(), self.tag_lo(), self.tag_hi())
+    }
+
     /// Set the tag in the low position.
+    ///
+    /// In debug builds this panics if the alignment of `V` doesn't free up enough low bits for the tag.
     pub fn with_tag_lo(self, tag: T1) -> Self {
+        debug_assert!(fits_lo::<V, T1>(), "low tag does not fit in the pointer");
         let bits = tag.serialize();
         let data = set_tag::<T1>(self.data, bits, TagPosition::Lo);
         unsafe { Self::from_raw(data) }
@@ -187,6 +274,45 @@ where
 {
 }
 
+/// Pointers are ordered by the address they point to with tags stripped.
+/// Pointers with the same address but different tags are ordered by their raw value so they end up next to each other.
+/// This ordering has no semantic meaning and is only useful for things like acquiring objects in a consistent order.
+impl<'shield, V, T1, T2> PartialOrd for Shared<'shield, V, T1, T2>
+where
+    V: 'shield,
+    T1: Tag,
+    T2: Tag,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'shield, V, T1, T2> Ord for Shared<'shield, V, T1, T2>
+where
+    V: 'shield,
+    T1: Tag,
+    T2: Tag,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.addr()
+            .cmp(&other.addr())
+            .then_with(|| self.into_raw().cmp(&other.into_raw()))
+    }
+}
+
+/// Pointers are hashed by the address they point to with tags stripped.
+impl<'shield, V, T1, T2> Hash for Shared<'shield, V, T1, T2>
+where
+    V: 'shield,
+    T1: Tag,
+    T2: Tag,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.addr().hash(state);
+    }
+}
+
 impl<'shield, V, T1, T2> Debug for Shared<'shield, V, T1, T2>
 where
     V: 'shield,
@@ -198,9 +324,12 @@ where
     }
 }
 
+// A `Shared` is semantically a shared reference to `V` so it has the same thread safety as `&V`.
+// Sending it to another thread is sound since the `'shield` lifetime still ties it to the shield
+// that protects the pointee. To hand popped values to other threads hold a `FullShield` for as long as they are used.
 unsafe impl<'shield, V, T1, T2> Send for Shared<'shield, V, T1, T2>
 where
-    V: 'shield,
+    V: 'shield + Sync,
     T1: Tag,
     T2: Tag,
 {
@@ -208,7 +337,7 @@ where
 
 unsafe impl<'shield, V, T1, T2> Sync for Shared<'shield, V, T1, T2>
 where
-    V: 'shield,
+    V: 'shield + Sync,
     T1: Tag,
     T2: Tag,
 {
@@ -221,3 +350,82 @@ where
     T2: Tag,
 {
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Shared;
+    use crate::{NullTag, Tag};
+    use generic_array::{typenum::U1, GenericArray};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Flag(bool);
+
+    impl Tag for Flag {
+        type Size = U1;
+
+        fn deserialize(bits: GenericArray<bool, Self::Size>) -> Self {
+            Flag(bits[0])
+        }
+
+        fn serialize(self) -> GenericArray<bool, Self::Size> {
+            let mut bits = GenericArray::default();
+            bits[0] = self.0;
+            bits
+        }
+    }
+
+    #[test]
+    fn into_owned() {
+        let ptr = Box::into_raw(Box::new(7_u64));
+        let shared: Shared<'_, u64> = unsafe { Shared::from_ptr(ptr) };
+        let owned = unsafe { shared.into_owned() };
+        assert_eq!(*owned, 7);
+    }
+
+    #[test]
+    fn order_by_address() {
+        let a: Shared<'_, u64> = unsafe { Shared::from_raw(8) };
+        let b: Shared<'_, u64> = unsafe { Shared::from_raw(16) };
+        assert!(a < b);
+        assert_eq!(a.max(b), b);
+    }
+
+    #[test]
+    fn covariant_lifetime() {
+        fn shorten<'short, 'long: 'short>(shared: Shared<'long, u64>) -> Shared<'short, u64> {
+            shared
+        }
+
+        let shared: Shared<'static, u64> = Shared::null();
+        assert!(shorten(shared).is_null());
+    }
+
+    #[test]
+    fn decompose() {
+        let shared: Shared<'_, u64, Flag, NullTag> = unsafe { Shared::from_raw(0x1000) };
+        let (ptr, lo, _) = shared.with_tag_lo(Flag(true)).decompose();
+        assert_eq!(ptr, shared);
+        assert_eq!(lo, Flag(true));
+        assert_eq!(ptr.with_tag_lo(lo).into_raw(), 0x1001);
+    }
+
+    #[test]
+    fn raw_parts_round_trip() {
+        let shared: Shared<'_, u64, Flag, NullTag> =
+            unsafe { Shared::from_raw_parts(0x1000, Flag(true), NullTag) };
+
+        assert_eq!(shared.into_raw(), 0x1001);
+        let (addr, lo, _) = shared.into_raw_parts();
+        assert_eq!(addr, 0x1000);
+        assert_eq!(lo, Flag(true));
+    }
+
+    #[test]
+    fn identity_ignores_tags() {
+        let shared: Shared<'_, u64, Flag, NullTag> = unsafe { Shared::from_raw(0x1000) };
+        let tagged = shared.with_tag_lo(Flag(true));
+        assert_eq!(tagged.addr(), 0x1000);
+        assert!(Shared::ptr_eq(shared, tagged));
+        assert_ne!(shared, tagged);
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{Atomic, Backoff, Shared, Shield};
+use core::fmt;
+use core::marker::PhantomData;
+use core::sync::atomic::Ordering;
+
+/// A node in the linked list backing the stack.
+struct Node<T> {
+    /// The value stored in this node.
+    value: T,
+
+    /// The node below this one.
+    next: Atomic<Node<T>>,
+}
+
+impl<T> Node<T> {
+    /// Allocates a new node that isn't linked to anything.
+    fn new(value: T) -> *mut Self {
+        let node = Self {
+            value,
+            next: Atomic::null(),
+        };
+
+        Box::into_raw(Box::new(node))
+    }
+
+    /// Drops the value and deallocates the node.
+    ///
+    /// # Safety
+    /// The pointer must have been created by `Node::new` and may not be used again.
+    unsafe fn destroy(this: *mut Self) {
+        drop(Box::from_raw(this));
+    }
+}
+
+/// A lock-free stack based on the algorithm described by R. Kent Treiber.
+///
+/// The stack is a singly linked list where the head pointer is swapped with a compare-and-swap.
+/// Nodes that have been popped are retired through the shield used to pop them and
+/// will be destroyed once no shield can hold a reference to them anymore.
+///
+/// # Examples
+/// ```
+/// use flize::{Collector, Stack};
+///
+/// let collector = Collector::new();
+/// let stack = Stack::new();
+/// let shield = collector.thin_shield();
+///
+/// stack.push(1, &shield);
+/// stack.push(2, &shield);
+///
+/// let top = stack.pop(&shield).unwrap();
+/// assert_eq!(unsafe { *top.as_ref_unchecked() }, 2);
+/// ```
+pub struct Stack<T> {
+    /// The top of the stack.
+    head: Atomic<Node<T>>,
+
+    /// Indicates that dropping a `Stack<T>` may drop values of type `T`.
+    _m0: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for Stack<T> {}
+unsafe impl<T: Send + Sync> Sync for Stack<T> {}
+
+impl<T> Stack<T> {
+    /// Creates a new empty stack.
+    pub fn new() -> Self {
+        Self {
+            head: Atomic::null(),
+            _m0: PhantomData,
+        }
+    }
+
+    /// Pushes a value onto the top of the stack.
+    pub fn push<'collector, S>(&self, value: T, shield: &S)
+    where
+        S: Shield<'collector>,
+    {
+        let backoff = Backoff::new();
+        let new = unsafe { Shared::from_ptr(Node::new(value)) };
+        let mut head = self.head.load(Ordering::Relaxed, shield);
+
+        loop {
+            // The node is not published yet so we are free to modify it.
+            unsafe {
+                new.as_ref_unchecked().next.store(head, Ordering::Relaxed);
+            }
+
+            match self.head.compare_exchange_weak(
+                head,
+                new,
+                Ordering::Release,
+                Ordering::Relaxed,
+                shield,
+            ) {
+                Ok(_) => return,
+                Err(current) => {
+                    head = current;
+                    backoff.spin();
+                }
+            }
+        }
+    }
+
+    /// Removes the value at the top of the stack.
+    ///
+    /// The value is not moved out of the stack, instead a pointer to it is returned.
+    /// The value is dropped once the node holding it has been reclaimed
+    /// so the returned pointer is only valid for as long as the shield is.
+    pub fn pop<'collector, 'shield, S>(&self, shield: &'shield S) -> Option<Shared<'shield, T>>
+    where
+        S: Shield<'collector>,
+        T: 'collector,
+    {
+        let backoff = Backoff::new();
+
+        loop {
+            let head = self.head.load(Ordering::Acquire, shield);
+            let node = unsafe { head.as_ref()? };
+            let next = node.next.load(Ordering::Relaxed, shield);
+
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::Release, Ordering::Relaxed, shield)
+                .is_ok()
+            {
+                // the node is unlinked so we can retire it, the shield keeps it alive for us
+                let ptr = head.as_ptr();
+                shield.retire(move || unsafe { Node::destroy(ptr) });
+                let value = &node.value as *const T as *mut T;
+                return Some(unsafe { Shared::from_ptr(value) });
+            }
+
+            backoff.spin();
+        }
+    }
+
+    /// Returns true if the stack contains no elements.
+    pub fn is_empty<'collector, S>(&self, shield: &S) -> bool
+    where
+        S: Shield<'collector>,
+    {
+        self.head.load(Ordering::Acquire, shield).is_null()
+    }
+}
+
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        // we have exclusive access to the stack so nothing can be reading the nodes
+        unsafe {
+            crate::unprotected_scope(|shield| {
+                let mut current = self.head.load(Ordering::Relaxed, shield);
+
+                while let Some(node) = current.as_ref() {
+                    let next = node.next.load(Ordering::Relaxed, shield);
+                    Node::destroy(current.as_ptr());
+                    current = next;
+                }
+            });
+        }
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Stack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Stack { .. }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stack;
+    use crate::Collector;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn push_pop_order() {
+        let collector = Collector::new();
+        let stack = Stack::new();
+        let shield = collector.thin_shield();
+
+        for i in 0..10 {
+            stack.push(i, &shield);
+        }
+
+        for i in (0..10).rev() {
+            let value = stack.pop(&shield).unwrap();
+            assert_eq!(unsafe { *value.as_ref_unchecked() }, i);
+        }
+
+        assert!(stack.is_empty(&shield));
+        assert!(stack.pop(&shield).is_none());
+    }
+
+    #[test]
+    fn concurrent_push_pop() {
+        const THREADS: usize = 8;
+        #[cfg(not(miri))]
+        const ITER: usize = 1000;
+        #[cfg(miri)]
+        const ITER: usize = 50;
+
+        let collector = Arc::new(Collector::new());
+        let stack = Arc::new(Stack::new());
+        let mut handles = Vec::new();
+
+        for _ in 0..THREADS {
+            let collector = Arc::clone(&collector);
+            let stack = Arc::clone(&stack);
+
+            handles.push(thread::spawn(move || {
+                let mut sum = 0;
+
+                for i in 0..ITER {
+                    let shield = collector.thin_shield();
+                    stack.push(i, &shield);
+                    let value = stack.pop(&shield).unwrap();
+                    sum += unsafe { *value.as_ref_unchecked() };
+                }
+
+                sum
+            }));
+        }
+
+        let sum: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        assert_eq!(sum, THREADS * (0..ITER).sum::<usize>());
+        assert!(stack.is_empty(&collector.thin_shield()));
+    }
+}
@@ -28,15 +28,21 @@ impl TagPosition {
 /// Zeroes all the tag bits.
 pub fn strip<T1: Tag, T2: Tag>(data: usize) -> usize {
     // mask for zeroing the low tag
-    let mask1: usize = core::usize::MAX >> <T1::Size as Unsigned>::to_usize();
+    let mask1: usize = core::usize::MAX << <T1::Size as Unsigned>::to_usize();
 
     // mask for zeroing the high tag
-    let mask2: usize = core::usize::MAX << <T2::Size as Unsigned>::to_usize();
+    let mask2: usize = core::usize::MAX >> <T2::Size as Unsigned>::to_usize();
 
     // apply the masks with an AND to zero the bits
     data & mask1 & mask2
 }
 
+/// Checks if a tag in the low position fits in the bits freed up by the alignment of `V`.
+pub fn fits_lo<V, T: Tag>() -> bool {
+    let free_bits = mem::align_of::<V>().trailing_zeros() as usize;
+    <T::Size as Unsigned>::to_usize() <= free_bits
+}
+
 /// Read the bits of a tag a a certain position.
 pub fn read_tag<T: Tag>(data: usize, position: TagPosition) -> GenericArray<bool, T::Size> {
     let to_skip = position.to_skip::<T>();
@@ -45,8 +51,7 @@ pub fn read_tag<T: Tag>(data: usize, position: TagPosition) -> GenericArray<bool
     array
         .iter_mut()
         .enumerate()
-        .skip(to_skip)
-        .for_each(|(index, bit)| *bit = ((data >> index) & 1) == 1);
+        .for_each(|(index, bit)| *bit = ((data >> (index + to_skip)) & 1) == 1);
 
     array
 }
@@ -59,13 +64,11 @@ pub fn set_tag<T: Tag>(
 ) -> usize {
     let to_skip = position.to_skip::<T>();
 
-    bits.iter()
-        .enumerate()
-        .skip(to_skip)
-        .for_each(|(index, bit)| {
-            let value = if *bit { 1 } else { 0 };
-            data = (data & !(1 << index)) | (value << index);
-        });
+    bits.iter().enumerate().for_each(|(index, bit)| {
+        let value = if *bit { 1 } else { 0 };
+        let offset = index + to_skip;
+        data = (data & !(1 << offset)) | (value << offset);
+    });
 
     data
 }
@@ -88,6 +91,46 @@ pub fn set_tag<T: Tag>(
 /// available bits. With pointer authentication you can only reasonably assume you have 0 available
 /// bits unless you know otherwise for your compiler. On all other architectures assume you have
 /// 0 available bits unless you know otherwise.
+///
+/// # Examples
+/// A tag that stores two independent flags in the low bits of a pointer.
+/// ```
+/// use flize::{Shared, Tag};
+/// use generic_array::{typenum::U2, GenericArray};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Flags {
+///     deleted: bool,
+///     helping: bool,
+/// }
+///
+/// impl Tag for Flags {
+///     type Size = U2;
+///
+///     fn deserialize(bits: GenericArray<bool, Self::Size>) -> Self {
+///         Flags {
+///             deleted: bits[0],
+///             helping: bits[1],
+///         }
+///     }
+///
+///     fn serialize(self) -> GenericArray<bool, Self::Size> {
+///         let mut bits = GenericArray::default();
+///         bits[0] = self.deleted;
+///         bits[1] = self.helping;
+///         bits
+///     }
+/// }
+///
+/// let flags = Flags {
+///     deleted: true,
+///     helping: false,
+/// };
+///
+/// let shared: Shared<'_, u64, Flags> = Shared::null().with_tag_lo(flags);
+/// assert_eq!(shared.tag_lo(), flags);
+/// assert!(shared.strip().is_null());
+/// ```
 pub trait Tag: Copy {
     /// The size in bits of the tag.
     type Size: ArrayLength<bool>;
@@ -115,3 +158,46 @@ impl Tag for NullTag {
         GenericArray::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{read_tag, set_tag, strip, NullTag, Tag, TagPosition};
+    use generic_array::{typenum::U2, GenericArray};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Pair(bool, bool);
+
+    impl Tag for Pair {
+        type Size = U2;
+
+        fn deserialize(bits: GenericArray<bool, Self::Size>) -> Self {
+            Pair(bits[0], bits[1])
+        }
+
+        fn serialize(self) -> GenericArray<bool, Self::Size> {
+            let mut bits = GenericArray::default();
+            bits[0] = self.0;
+            bits[1] = self.1;
+            bits
+        }
+    }
+
+    #[test]
+    fn low_round_trip() {
+        let data = set_tag::<Pair>(0x1000, Pair(true, false).serialize(), TagPosition::Lo);
+        assert_eq!(data, 0x1001);
+        let tag = Pair::deserialize(read_tag::<Pair>(data, TagPosition::Lo));
+        assert_eq!(tag, Pair(true, false));
+        assert_eq!(strip::<Pair, NullTag>(data), 0x1000);
+    }
+
+    #[test]
+    fn high_round_trip() {
+        let top = 1 << (core::mem::size_of::<usize>() * 8 - 1);
+        let data = set_tag::<Pair>(0x1000, Pair(false, true).serialize(), TagPosition::Hi);
+        assert_eq!(data, 0x1000 | top);
+        let tag = Pair::deserialize(read_tag::<Pair>(data, TagPosition::Hi));
+        assert_eq!(tag, Pair(false, true));
+        assert_eq!(strip::<NullTag, Pair>(data), 0x1000);
+    }
+}
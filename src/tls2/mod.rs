@@ -26,11 +26,32 @@ impl<T> ThreadLocal<T> {
         }
     }
 
+    /// Get the entry of the current thread, creating it if it doesn't exist.
+    ///
+    /// Panics if more than `MAX_THREADS` threads are alive at the same time.
     pub fn get<F>(&self, create: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        self.try_get(create)
+            .expect("too many threads are registered with the thread local")
+    }
+
+    /// Get the entry of the current thread, creating it if it doesn't exist.
+    ///
+    /// Returns `None` if the id of the current thread doesn't fit in the table which happens
+    /// when more than `MAX_THREADS` threads are alive at the same time. Thread ids are reused so
+    /// a thread that got an entry will never fail.
+    pub fn try_get<F>(&self, create: F) -> Option<&T>
     where
         F: FnOnce() -> T,
     {
         let id = thread_id::get();
+
+        if id >= MAX_THREADS {
+            return None;
+        }
+
         let entry = unsafe { self.entries.get_unchecked(id).load(Ordering::SeqCst) };
 
         if entry == 0 {
@@ -41,10 +62,22 @@ impl<T> ThreadLocal<T> {
                 self.entries.get_unchecked(id).store(raw, Ordering::SeqCst);
             }
             self.snapshot.fetch_add(1, Ordering::SeqCst);
-            unsafe { &*(raw as *const T) }
+            unsafe { Some(&*(raw as *const T)) }
         } else {
-            unsafe { &*(entry as *const T) }
+            unsafe { Some(&*(entry as *const T)) }
+        }
+    }
+
+    /// Get the entry of the current thread without creating it if it doesn't exist.
+    pub fn get_existing(&self) -> Option<&T> {
+        let id = thread_id::get();
+
+        if id >= MAX_THREADS {
+            return None;
         }
+
+        let entry = unsafe { self.entries.get_unchecked(id).load(Ordering::SeqCst) };
+        unsafe { (entry as *const T).as_ref() }
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
@@ -61,6 +94,30 @@ impl<T> ThreadLocal<T> {
     pub fn changed_since(&self, snapshot: Snapshot) -> bool {
         self.snapshot.load(Ordering::SeqCst) != snapshot.0
     }
+
+    /// Removes and drops all entries.
+    ///
+    /// # Safety
+    /// No other thread may access the table during the call and no references
+    /// previously handed out by `ThreadLocal::get` or `ThreadLocal::iter` may be used afterwards.
+    pub unsafe fn clear(&self) {
+        for entry in self.entries.iter() {
+            let raw = entry.swap(0, Ordering::SeqCst);
+
+            if raw != 0 {
+                drop(Box::from_raw(raw as *mut T));
+            }
+        }
+    }
+}
+
+impl<T> Drop for ThreadLocal<T> {
+    fn drop(&mut self) {
+        // we have exclusive access so nothing else can be using the entries
+        unsafe {
+            self.clear();
+        }
+    }
 }
 
 unsafe impl<T> Send for ThreadLocal<T> where T: Send {}